@@ -0,0 +1,737 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::derived_key_lib::{self, DerivedKeyEntry, TransactionSpendingLimit};
+use crate::errors;
+use crate::key_lib::PublicKey;
+use crate::post_lib::{self, PostEntryResponse, SinglePostOptions, SubmitPostDataBuilder};
+use crate::signer_lib::Signer;
+use crate::submit_lib::{Sleeper, SubmissionOutcome, SubmitPolicy, TokioSleeper};
+use crate::txn_lib::{MsgDeSoTxn, TxnExpectations};
+use crate::{
+    poll_transaction, query_node, submit_and_sign, submit_signed, DesoAccount, Node,
+    TransactionType,
+};
+
+/// Extra check run on every node built transaction before it gets signed.
+/// Return an error to refuse signing.
+pub type TxnVerifier = Arc<dyn Fn(&MsgDeSoTxn) -> Result<(), errors::DesoError> + Send + Sync>;
+
+/// Min fee rate nanos per kb used when neither the client nor the request sets one
+pub const DEFAULT_FEE_RATE: u64 = 1250;
+
+/// A long lived client for talking to a Deso node.
+///
+/// Holds a shared `reqwest::Client` so connection pools and TLS sessions are
+/// reused across transactions. Cloning a `DesoClient` is cheap and shares the
+/// same connection pool.
+///
+/// The crate's free functions, such as `create_post` or `like_post`, run on a
+/// default `DesoClient` for the account's node, all sharing one connection
+/// pool. Build a client instead to pick the fee rate, signer or submit policy.
+#[derive(Clone)]
+pub struct DesoClient {
+    pub(crate) http: reqwest::Client,
+    pub(crate) node: Node,
+    pub(crate) fee_rate: u64,
+    pub(crate) account: Option<Arc<DesoAccount>>,
+    pub(crate) signer: Option<Arc<dyn Signer>>,
+    pub(crate) sleeper: Arc<dyn Sleeper>,
+    pub(crate) policy: SubmitPolicy,
+    pub(crate) max_fee_nanos: Option<u64>,
+    pub(crate) txn_verifier: Option<TxnVerifier>,
+    pub(crate) remote_signature_index: bool,
+}
+
+/// Builder for a `DesoClient`
+pub struct DesoClientBuilder {
+    /// The Node you are targeting (defaults to the account's node, or Main)
+    pub node: Option<Node>,
+    /// Timeout for a whole request, including reading the response
+    pub timeout: Option<Duration>,
+    /// Timeout for establishing a connection to the node
+    pub connect_timeout: Option<Duration>,
+    /// Default min fee rate nanos per kb, defaults to 1250
+    pub fee_rate: Option<u64>,
+    /// The account used to sign transactions sent through the client
+    pub account: Option<DesoAccount>,
+    /// Signs transactions sent through the client in place of the account's seed
+    pub signer: Option<Arc<dyn Signer>>,
+    /// Waits between retries and confirmation checks, defaults to `TokioSleeper`
+    pub sleeper: Option<Arc<dyn Sleeper>>,
+    /// Retry and confirmation policy for submitted transactions
+    pub submit_policy: Option<SubmitPolicy>,
+    /// Refuse to sign node built transactions paying more than this fee
+    pub max_fee_nanos: Option<u64>,
+    /// Extra check run on every node built transaction before it gets signed
+    pub txn_verifier: Option<TxnVerifier>,
+    /// Ask the node's `signature-index` endpoint where the signature goes
+    /// instead of working it out locally, defaults to false
+    pub remote_signature_index: Option<bool>,
+}
+
+impl Default for DesoClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DesoClientBuilder {
+    pub fn new() -> Self {
+        DesoClientBuilder {
+            node: None,
+            timeout: None,
+            connect_timeout: None,
+            fee_rate: Some(DEFAULT_FEE_RATE),
+            account: None,
+            signer: None,
+            sleeper: None,
+            submit_policy: None,
+            max_fee_nanos: None,
+            txn_verifier: None,
+            remote_signature_index: None,
+        }
+    }
+    /// The Node you are targeting (defaults to the account's node, or Main)
+    pub fn node(mut self, node: Node) -> Self {
+        self.node = Some(node);
+        self
+    }
+    /// Timeout for a whole request, including reading the response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    /// Timeout for establishing a connection to the node
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+    /// Default min fee rate nanos per kb, defaults to 1250
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+    /// The account used to sign transactions sent through the client
+    pub fn account(mut self, account: DesoAccount) -> Self {
+        self.account = Some(account);
+        self
+    }
+    /// Signs transactions sent through the client in place of the account's seed
+    pub fn signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+    /// Waits between retries and confirmation checks, defaults to `TokioSleeper`
+    pub fn sleeper(mut self, sleeper: Arc<dyn Sleeper>) -> Self {
+        self.sleeper = Some(sleeper);
+        self
+    }
+    /// Retry and confirmation policy for submitted transactions
+    pub fn submit_policy(mut self, submit_policy: SubmitPolicy) -> Self {
+        self.submit_policy = Some(submit_policy);
+        self
+    }
+    /// Refuse to sign node built transactions paying more than this fee
+    pub fn max_fee_nanos(mut self, max_fee_nanos: u64) -> Self {
+        self.max_fee_nanos = Some(max_fee_nanos);
+        self
+    }
+    /// Extra check run on every node built transaction before it gets signed
+    pub fn txn_verifier(mut self, txn_verifier: TxnVerifier) -> Self {
+        self.txn_verifier = Some(txn_verifier);
+        self
+    }
+    /// Ask the node's `signature-index` endpoint where the signature goes
+    /// instead of working it out locally, defaults to false
+    pub fn remote_signature_index(mut self, remote_signature_index: bool) -> Self {
+        self.remote_signature_index = Some(remote_signature_index);
+        self
+    }
+    /// Builds the DesoClient
+    pub fn build(self) -> Result<DesoClient, errors::DesoError> {
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        let http = match http.build() {
+            Ok(c) => c,
+            Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
+        };
+        let node = match (self.node, &self.account) {
+            (Some(node), _) => node,
+            (None, Some(account)) => account.node.clone(),
+            (None, None) => Node::MAIN,
+        };
+        let account = self.account.map(Arc::new);
+        let signer = match (self.signer, &account) {
+            (Some(signer), _) => Some(signer),
+            (None, Some(account)) => Some(account.clone() as Arc<dyn Signer>),
+            (None, None) => None,
+        };
+        Ok(DesoClient {
+            http,
+            node,
+            fee_rate: self.fee_rate.unwrap_or(DEFAULT_FEE_RATE),
+            account,
+            signer,
+            sleeper: self.sleeper.unwrap_or_else(|| Arc::new(TokioSleeper)),
+            policy: self.submit_policy.unwrap_or_default(),
+            max_fee_nanos: self.max_fee_nanos,
+            txn_verifier: self.txn_verifier,
+            remote_signature_index: self.remote_signature_index.unwrap_or(false),
+        })
+    }
+}
+
+impl DesoClient {
+    /// A client for `node` with default settings and no account
+    pub fn new(node: Node) -> Self {
+        Self::with_http(node, reqwest::Client::new())
+    }
+
+    /// Same as `new`, but every client made this way shares one connection
+    /// pool. Backs the crate's free functions.
+    pub(crate) fn shared(node: Node) -> Self {
+        static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+        Self::with_http(node, HTTP.get_or_init(reqwest::Client::new).clone())
+    }
+
+    fn with_http(node: Node, http: reqwest::Client) -> Self {
+        DesoClient {
+            http,
+            node,
+            fee_rate: DEFAULT_FEE_RATE,
+            account: None,
+            signer: None,
+            sleeper: Arc::new(TokioSleeper),
+            policy: SubmitPolicy::default(),
+            max_fee_nanos: None,
+            txn_verifier: None,
+            remote_signature_index: false,
+        }
+    }
+
+    /// A copy of this client, sharing its connection pool, that submits with `policy`.
+    ///
+    /// Use this to override the policy for a single call:
+    /// `client.with_submit_policy(policy).create_post(&post_data)`.
+    pub fn with_submit_policy(&self, policy: SubmitPolicy) -> Self {
+        DesoClient {
+            policy,
+            ..self.clone()
+        }
+    }
+
+    /// The retry and confirmation policy used for submitted transactions
+    pub fn submit_policy(&self) -> &SubmitPolicy {
+        &self.policy
+    }
+
+    /// The Node this client is targeting
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// The account used to sign transactions, if one was set
+    pub fn account(&self) -> Option<&DesoAccount> {
+        self.account.as_deref()
+    }
+
+    /// The signer used for transactions sent through the client: the one
+    /// set on the builder, else the account
+    pub fn signer(&self) -> Option<&dyn Signer> {
+        self.signer.as_deref()
+    }
+
+    fn require_signer(&self) -> Result<&dyn Signer, errors::DesoError> {
+        match self.signer() {
+            Some(signer) => Ok(signer),
+            None => Err(errors::DesoError::DesoAccountError(String::from(
+                "Client Account",
+            ))),
+        }
+    }
+
+    /// Creates a new post or comment signed by the client's signer
+    pub async fn create_post(
+        &self,
+        post_data: &post_lib::SubmitPostData,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.create_post_as(signer, post_data).await
+    }
+
+    /// Creates a new post or comment signed by `publisher`
+    pub async fn create_post_as(
+        &self,
+        publisher: &dyn Signer,
+        post_data: &post_lib::SubmitPostData,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let post_uri = self.node.get_endpoint("api/v0/submit-post");
+
+        if publisher.public_key() != &post_data.public_key {
+            return Err(errors::DesoError::SignerMismatch(format!(
+                "{} can't sign a post for {}",
+                publisher.public_key(),
+                post_data.public_key
+            )));
+        }
+        post_data.public_key.check_network(self.node.network())?;
+        let mut post_data = post_data.clone();
+        post_data.fee_rate.get_or_insert(self.fee_rate);
+        let expected_post = post_data.expectations()?;
+
+        let (post_transaction_response, outcome) = submit_and_sign(
+            self,
+            &post_uri,
+            &post_data,
+            TransactionType::POST,
+            publisher,
+            TxnExpectations {
+                public_key: Some(post_data.public_key.as_bytes().to_vec()),
+                post: Some(expected_post),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let mut transaction_json: post_lib::SubmittedTransaction =
+            match serde_json::from_str(&post_transaction_response) {
+                Ok(j) => j,
+                Err(e) => {
+                    return Err(errors::DesoError::JsonError(
+                        String::from("NEW POST ERROR"),
+                        e.to_string(),
+                    ))
+                }
+            };
+        transaction_json.outcome = Some(outcome);
+
+        Ok(transaction_json)
+    }
+
+    /// Reposts the post with hash `post_hash_hex`, signed by the client's signer
+    pub async fn repost(
+        &self,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.repost_as(signer, post_hash_hex).await
+    }
+
+    /// Reposts the post with hash `post_hash_hex`, signed by `reposter`
+    pub async fn repost_as(
+        &self,
+        reposter: &dyn Signer,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let post_data = SubmitPostDataBuilder::new()
+            .public_key(reposter.public_key().clone())
+            .repost(post_hash_hex.to_string())
+            .build()?;
+        self.create_post_as(reposter, &post_data).await
+    }
+
+    /// Quotes the post with hash `post_hash_hex` with `body`, signed by the
+    /// client's signer
+    pub async fn quote_repost(
+        &self,
+        post_hash_hex: &str,
+        body: String,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.quote_repost_as(signer, post_hash_hex, body).await
+    }
+
+    /// Quotes the post with hash `post_hash_hex` with `body`, signed by `reposter`
+    pub async fn quote_repost_as(
+        &self,
+        reposter: &dyn Signer,
+        post_hash_hex: &str,
+        body: String,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let post_data = SubmitPostDataBuilder::new()
+            .public_key(reposter.public_key().clone())
+            .quote_repost(post_hash_hex.to_string())
+            .body(body)
+            .build()?;
+        self.create_post_as(reposter, &post_data).await
+    }
+
+    /// Likes the post with hash `post_hash_hex`, or takes the like back if
+    /// `unlike` is set, signed by the client's signer
+    pub async fn like_post(
+        &self,
+        post_hash_hex: &str,
+        unlike: bool,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.like_post_as(signer, post_hash_hex, unlike).await
+    }
+
+    /// Likes the post with hash `post_hash_hex`, or takes the like back if
+    /// `unlike` is set, signed by `reader`
+    pub async fn like_post_as(
+        &self,
+        reader: &dyn Signer,
+        post_hash_hex: &str,
+        unlike: bool,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        let liked_post_hash = post_lib::parse_post_hash_hex(post_hash_hex)?;
+        reader.public_key().check_network(self.node.network())?;
+        let request = post_lib::CreateLikeRequest {
+            reader_public_key: reader.public_key().clone(),
+            liked_post_hash_hex: post_hash_hex.to_string(),
+            is_unlike: unlike,
+            fee_rate: self.fee_rate,
+        };
+        let uri = self.node.get_endpoint("api/v0/create-like-stateless");
+        let (_, outcome) = submit_and_sign(
+            self,
+            &uri,
+            &request,
+            TransactionType::LIKE,
+            reader,
+            TxnExpectations {
+                public_key: Some(reader.public_key().as_bytes().to_vec()),
+                liked_post_hash: Some(liked_post_hash),
+                is_unlike: Some(unlike),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(outcome)
+    }
+
+    /// Whether `reader` currently likes the post with hash `post_hash_hex`
+    pub async fn has_liked_post(
+        &self,
+        reader: &PublicKey,
+        post_hash_hex: &str,
+    ) -> Result<bool, errors::DesoError> {
+        let options = SinglePostOptions {
+            reader_public_key: Some(reader.clone()),
+            comment_limit: 0,
+            ..Default::default()
+        };
+        let post = self.get_single_post(post_hash_hex, &options).await?;
+        Ok(post
+            .reader_state
+            .is_some_and(|reader_state| reader_state.liked_by_reader))
+    }
+
+    /// Edits the post named by `post_data.post_hash_hex_to_modify`, signed by
+    /// the client's signer
+    pub async fn edit_post(
+        &self,
+        post_data: &post_lib::SubmitPostData,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.edit_post_as(signer, post_data).await
+    }
+
+    /// Edits the post named by `post_data.post_hash_hex_to_modify`, signed by
+    /// `editor`.
+    ///
+    /// Fetches the post first and refuses to edit posts made by anyone other
+    /// than `post_data.public_key`. Extra data keys missing from `post_data`
+    /// keep their current values.
+    pub async fn edit_post_as(
+        &self,
+        editor: &dyn Signer,
+        post_data: &post_lib::SubmitPostData,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let post_hash_hex = match &post_data.post_hash_hex_to_modify {
+            Some(h) => h,
+            None => {
+                return Err(errors::DesoError::SubmitPostDataBuilderError(String::from(
+                    "Post Hash Hex To Modify",
+                )))
+            }
+        };
+        if editor.public_key() != &post_data.public_key {
+            return Err(errors::DesoError::PostEditError(format!(
+                "{} can't sign an edit for {}",
+                editor.public_key(),
+                post_data.public_key
+            )));
+        }
+        let options = SinglePostOptions {
+            comment_limit: 0,
+            ..Default::default()
+        };
+        let original = self.get_single_post(post_hash_hex, &options).await?;
+        self.edit_post_from(editor, &original, post_data.clone())
+            .await
+    }
+
+    /// Hides the post with hash `post_hash_hex`, the closest Deso has to
+    /// deleting it, signed by the client's signer
+    pub async fn hide_post(
+        &self,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.set_post_hidden(signer, post_hash_hex, true).await
+    }
+
+    /// Hides the post with hash `post_hash_hex`, signed by `poster`
+    pub async fn hide_post_as(
+        &self,
+        poster: &dyn Signer,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        self.set_post_hidden(poster, post_hash_hex, true).await
+    }
+
+    /// Shows a hidden post with hash `post_hash_hex` again, signed by the
+    /// client's signer
+    pub async fn unhide_post(
+        &self,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let signer = self.require_signer()?;
+        self.set_post_hidden(signer, post_hash_hex, false).await
+    }
+
+    /// Shows a hidden post with hash `post_hash_hex` again, signed by `poster`
+    pub async fn unhide_post_as(
+        &self,
+        poster: &dyn Signer,
+        post_hash_hex: &str,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        self.set_post_hidden(poster, post_hash_hex, false).await
+    }
+
+    /// Resubmits the post unchanged apart from whether it is hidden
+    async fn set_post_hidden(
+        &self,
+        poster: &dyn Signer,
+        post_hash_hex: &str,
+        is_hidden: bool,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        let options = SinglePostOptions {
+            comment_limit: 0,
+            ..Default::default()
+        };
+        let original = self.get_single_post(post_hash_hex, &options).await?;
+        let mut post_data = post_lib::SubmitPostData::from_existing(&original)?;
+        post_data.public_key = poster.public_key().clone();
+        post_data.is_hidden = is_hidden;
+        self.edit_post_from(poster, &original, post_data).await
+    }
+
+    /// Submits `post_data` as an edit of `original`, after checking it is
+    /// the original poster's and carrying over its extra data. Callers check
+    /// `editor` signs for `post_data`
+    async fn edit_post_from(
+        &self,
+        editor: &dyn Signer,
+        original: &PostEntryResponse,
+        mut post_data: post_lib::SubmitPostData,
+    ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+        if original.poster_public_key != post_data.public_key.to_base58_check() {
+            return Err(errors::DesoError::PostEditError(format!(
+                "{} was posted by {}, not {}",
+                original.post_hash_hex, original.poster_public_key, post_data.public_key
+            )));
+        }
+        post_data.post_hash_hex_to_modify = Some(original.post_hash_hex.clone());
+        let mut extra_data = original.extra_data.clone();
+        extra_data.extend(post_data.extra_data.take().unwrap_or_default());
+        if !extra_data.is_empty() {
+            post_data.extra_data = Some(extra_data);
+        }
+        self.create_post_as(editor, &post_data).await
+    }
+
+    /// Fetches the post with hash `post_hash_hex`, along with its comments,
+    /// counts and, as `options` asks, its parent posts and reader state
+    pub async fn get_single_post(
+        &self,
+        post_hash_hex: &str,
+        options: &SinglePostOptions,
+    ) -> Result<PostEntryResponse, errors::DesoError> {
+        post_lib::parse_post_hash_hex(post_hash_hex)?;
+        if let Some(reader) = &options.reader_public_key {
+            reader.check_network(self.node.network())?;
+        }
+        let uri = self.node.get_endpoint("api/v0/get-single-post");
+        let request = post_lib::GetSinglePost {
+            post_hash_hex: post_hash_hex.to_string(),
+            reader_public_key: options.reader_public_key.clone(),
+            fetch_parents: options.fetch_parents,
+            comment_offset: options.comment_offset,
+            comment_limit: options.comment_limit,
+        };
+        let response: post_lib::SinglePostResponse =
+            query_node(self.http.post(uri).json(&request), "GET SINGLE POST").await?;
+        Ok(response.post)
+    }
+
+    /// Authorizes `derived_public_key` to sign for `owner` until `expiration_block`,
+    /// within `spending_limit`. The access signature is made locally with the
+    /// owner's seed hex, so `owner` can't be a derived key account.
+    pub async fn authorize_derived_key(
+        &self,
+        owner: &DesoAccount,
+        derived_public_key: &PublicKey,
+        expiration_block: u64,
+        spending_limit: &TransactionSpendingLimit,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        self.submit_authorization(
+            owner,
+            derived_public_key,
+            expiration_block,
+            spending_limit,
+            false,
+        )
+        .await
+    }
+
+    /// Revokes `derived_public_key`, so it can no longer sign for `owner`.
+    /// Errors if the node doesn't know the key.
+    pub async fn revoke_derived_key(
+        &self,
+        owner: &DesoAccount,
+        derived_public_key: &PublicKey,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        let entry = match self
+            .get_single_derived_key(&owner.public_key, derived_public_key)
+            .await?
+        {
+            Some(entry) => entry,
+            None => {
+                return Err(errors::DesoError::DesoError(format!(
+                    "{} is not a derived key of {}",
+                    derived_public_key, owner.public_key
+                )))
+            }
+        };
+        self.submit_authorization(
+            owner,
+            derived_public_key,
+            entry.expiration_block,
+            &TransactionSpendingLimit::default(),
+            true,
+        )
+        .await
+    }
+
+    /// Every derived key the node knows for `owner`, revoked and expired ones
+    /// included, ordered by derived public key
+    pub async fn get_user_derived_keys(
+        &self,
+        owner: &PublicKey,
+    ) -> Result<Vec<DerivedKeyEntry>, errors::DesoError> {
+        let uri = self.node.get_endpoint("api/v0/get-user-derived-keys");
+        let request = derived_key_lib::GetUserDerivedKeysRequest {
+            public_key: owner.clone(),
+        };
+        let response: derived_key_lib::GetUserDerivedKeysResponse =
+            query_node(self.http.post(uri).json(&request), "GET USER DERIVED KEYS").await?;
+        let mut entries: Vec<DerivedKeyEntry> = response
+            .derived_keys
+            .unwrap_or_default()
+            .into_values()
+            .collect();
+        entries.sort_by_key(|entry| entry.derived_public_key.to_base58_check());
+        Ok(entries)
+    }
+
+    /// The node's entry for `derived_public_key` under `owner`, if it has one
+    pub async fn get_single_derived_key(
+        &self,
+        owner: &PublicKey,
+        derived_public_key: &PublicKey,
+    ) -> Result<Option<DerivedKeyEntry>, errors::DesoError> {
+        let uri = self.node.get_endpoint(&format!(
+            "api/v0/get-single-derived-key/{}/{}",
+            owner, derived_public_key
+        ));
+        let response: derived_key_lib::GetSingleDerivedKeyResponse =
+            query_node(self.http.get(uri), "GET SINGLE DERIVED KEY").await?;
+        Ok(response.derived_key)
+    }
+
+    async fn submit_authorization(
+        &self,
+        owner: &DesoAccount,
+        derived_public_key: &PublicKey,
+        expiration_block: u64,
+        spending_limit: &TransactionSpendingLimit,
+        delete_key: bool,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        if owner.derived_public_key.is_some() {
+            return Err(errors::DesoError::SigningError(String::from(
+                "Authorizing a derived key needs the owner's seed hex, not a derived key",
+            )));
+        }
+        owner.public_key.check_network(self.node.network())?;
+        derived_public_key.check_network(self.node.network())?;
+
+        let access_signature = derived_key_lib::access_signature(
+            &owner.seed_hex_key,
+            derived_public_key,
+            expiration_block,
+            spending_limit,
+        );
+        let request = derived_key_lib::AuthorizeDerivedKeyRequest {
+            owner_public_key: owner.public_key.clone(),
+            derived_public_key: derived_public_key.clone(),
+            expiration_block,
+            access_signature: hex::encode(&access_signature),
+            delete_key,
+            derived_key_signature: false,
+            transaction_spending_limit_hex: hex::encode(spending_limit.to_bytes()),
+            fee_rate: self.fee_rate,
+        };
+        let uri = self.node.get_endpoint("api/v0/authorize-derived-key");
+        let (_, outcome) = submit_and_sign(
+            self,
+            &uri,
+            &request,
+            TransactionType::AUTHORIZE,
+            owner,
+            TxnExpectations {
+                public_key: Some(owner.public_key.as_bytes().to_vec()),
+                authorized_derived_public_key: Some(*derived_public_key.as_bytes()),
+                expiration_block: Some(expiration_block),
+                operation_type: Some(if delete_key { 0 } else { 1 }),
+                access_signature: Some(access_signature),
+                // Revoking a key grants nothing, so only authorizations carry a limit
+                spending_limit: (!delete_key).then(|| spending_limit.to_bytes()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(outcome)
+    }
+
+    /// Submits a transaction that was built and signed locally
+    pub async fn submit_signed_transaction(
+        &self,
+        txn: &MsgDeSoTxn,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        if txn.signature.is_none() {
+            return Err(errors::DesoError::SigningError(String::from(
+                "Transaction is not signed",
+            )));
+        }
+        let (_, outcome) = submit_signed(self, txn.to_hex()?, "signed").await?;
+        Ok(outcome)
+    }
+
+    /// Waits for a submitted transaction to show up on the node, following
+    /// `policy` for how often to check and for how long.
+    pub async fn wait_for_transaction(
+        &self,
+        txn_hash_hex: &str,
+        policy: &SubmitPolicy,
+    ) -> Result<SubmissionOutcome, errors::DesoError> {
+        poll_transaction(self, txn_hash_hex, policy).await
+    }
+}
@@ -1,54 +1,261 @@
 use crate::errors;
-use hex;
-use secp256k1::{Message, Secp256k1, SecretKey};
+use crate::key_lib::PrivateKey;
+use crate::txn_lib;
+use crate::Network;
+use bitcoin_hashes::{sha256d, Hash};
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Secp256k1};
 
-pub(crate) fn sign(
-    tx: String,
-    seed_hex: String,
+/// Writes `signature` into an unsigned transaction where its empty
+/// placeholder sits at `signature_index`, and returns the signed hex.
+pub(crate) fn insert_signature(
+    transaction_bytes: &[u8],
     signature_index: usize,
+    signature: &[u8],
 ) -> Result<String, errors::DesoError> {
-    // Get our curve set up
-    let secp = Secp256k1::new();
-
-    // First get the private key from seed hex
-    let private_key = hex::decode(seed_hex).expect("Error decoding seed hex");
-    let secret_key = SecretKey::from_slice(&private_key).unwrap();
-
-    // Get transaction bytes
-    let transaction_bytes = hex::decode(&tx).expect("Problem decoding transaction");
+    check_unsigned(transaction_bytes, signature_index)?;
+    if signature.is_empty() || signature.len() > MAX_DER_SIGNATURE_LEN {
+        return Err(errors::DesoError::SigningError(format!(
+            "A {} byte signature is not a DER signature",
+            signature.len()
+        )));
+    }
+    let v0_fields_without_signature = &transaction_bytes[..signature_index];
     let v1_fields_buffer = &transaction_bytes[signature_index + 1..];
-    let v0_fields_without_signature = &transaction_bytes[0..signature_index];
-
-    // Now double hash the bytes and store in Message struct
-    let message = Message::from_hashed_data::<bitcoin_hashes::sha256d::Hash>(&transaction_bytes);
-
-    // Sign the message with the private key
-    let signed_sig = secp.sign_ecdsa(&message, &secret_key);
 
-    // Convert to DER
-    let serialized_sig = signed_sig.serialize_der();
+    // The signature length is a uvarint, like every other length
+    let mut length = Vec::new();
+    txn_lib::encode_uvarint(signature.len() as u64, &mut length);
 
-    // Get the byte array of the signature
-    let serialized_bytes = serialized_sig.to_vec();
-
-    // Get the length of the signature
-    let length_bytes = usize::to_le_bytes(serialized_bytes.len());
-    let length: Vec<u8> = length_bytes
-        .iter()
-        .copied()
-        .filter(|&num| num != 0)
-        .collect();
-
-    // // Create new buffer
     let signed_transaction_bytes = [
         v0_fields_without_signature,
         &length,
-        &serialized_sig,
-        &v1_fields_buffer,
+        signature,
+        v1_fields_buffer,
     ]
     .concat();
 
-    let signed_txn_hex = hex::encode(signed_transaction_bytes);
+    Ok(hex::encode(signed_transaction_bytes))
+}
+
+/// Checks that `transaction_bytes` carry an empty signature at `signature_index`
+pub(crate) fn check_unsigned(
+    transaction_bytes: &[u8],
+    signature_index: usize,
+) -> Result<(), errors::DesoError> {
+    // An unsigned transaction carries a zero signature length at the index
+    match transaction_bytes.get(signature_index) {
+        Some(0) => Ok(()),
+        Some(_) => Err(errors::DesoError::SigningError(format!(
+            "Transaction already has a signature at index {}",
+            signature_index
+        ))),
+        None => Err(errors::DesoError::SigningError(format!(
+            "Signature index {} is outside the {} byte transaction",
+            signature_index,
+            transaction_bytes.len()
+        ))),
+    }
+}
+
+/// The longest a DER encoded secp256k1 signature can be
+pub(crate) const MAX_DER_SIGNATURE_LEN: usize = 72;
+
+/// Signs the double sha256 of `bytes` with `private_key`, returning the DER signature
+pub(crate) fn sign_bytes(bytes: &[u8], private_key: &PrivateKey) -> Vec<u8> {
+    let message = Message::from_hashed_data::<sha256d::Hash>(bytes);
+    let signature = Secp256k1::signing_only().sign_ecdsa(&message, &private_key.secret_key());
+    signature.serialize_der().to_vec()
+}
+
+/// Checks a DER signature over the double sha256 of `bytes` against a
+/// compressed or uncompressed public key
+pub(crate) fn verify_bytes(
+    bytes: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<(), errors::DesoError> {
+    let public_key = match PublicKey::from_slice(public_key) {
+        Ok(k) => k,
+        Err(e) => {
+            return Err(errors::DesoError::VerificationError(format!(
+                "Invalid public key: {}",
+                e
+            )))
+        }
+    };
+    // Derived key signatures may mark the DER header with a recovery id (0x31 - 0x34)
+    let mut der = signature.to_vec();
+    if let Some(header @ 0x31..=0x34) = der.first_mut() {
+        *header = 0x30;
+    }
+    let mut signature = match Signature::from_der(&der) {
+        Ok(s) => s,
+        Err(e) => {
+            return Err(errors::DesoError::VerificationError(format!(
+                "Invalid signature: {}",
+                e
+            )))
+        }
+    };
+    // Deso accepts high S signatures, libsecp256k1 only verifies low S ones
+    signature.normalize_s();
+    let message = Message::from_hashed_data::<sha256d::Hash>(bytes);
+    match Secp256k1::verification_only().verify_ecdsa(&message, &signature, &public_key) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(errors::DesoError::VerificationError(e.to_string())),
+    }
+}
+
+/// Base58Check prefix for mainnet keys and ids (`BC1YL...`)
+pub(crate) const MAINNET_PREFIX: [u8; 3] = [0xcd, 0x14, 0x00];
+/// Base58Check prefix for testnet keys and ids (`tBC...`)
+pub(crate) const TESTNET_PREFIX: [u8; 3] = [0x11, 0xc2, 0x00];
+
+pub(crate) fn network_prefix(network: Network) -> [u8; 3] {
+    match network {
+        Network::MAINNET => MAINNET_PREFIX,
+        Network::TESTNET => TESTNET_PREFIX,
+    }
+}
+
+/// Encodes `payload` as Deso Base58Check: prefix, payload and the first four
+/// bytes of the double sha256 of both.
+pub(crate) fn base58_check_encode(payload: &[u8], network: Network) -> String {
+    let mut bytes = network_prefix(network).to_vec();
+    bytes.extend_from_slice(payload);
+    let checksum = sha256d::Hash::hash(&bytes);
+    bytes.extend_from_slice(&checksum[..4]);
+    bs58::encode(bytes).into_string()
+}
+
+/// Decodes a Deso Base58Check string, checking its checksum, and returns the
+/// payload along with the network its prefix belongs to.
+pub(crate) fn base58_check_decode(encoded: &str) -> Result<(Vec<u8>, Network), errors::DesoError> {
+    let bytes = match bs58::decode(encoded).into_vec() {
+        Ok(b) => b,
+        Err(e) => return Err(errors::DesoError::PublicKeyError(e.to_string())),
+    };
+    if bytes.len() < 3 + 4 {
+        return Err(errors::DesoError::PublicKeyError(String::from("Too short")));
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 4);
+    if &sha256d::Hash::hash(body)[..4] != checksum {
+        return Err(errors::DesoError::PublicKeyError(String::from(
+            "Checksum mismatch",
+        )));
+    }
+    let network = if body[..3] == MAINNET_PREFIX {
+        Network::MAINNET
+    } else if body[..3] == TESTNET_PREFIX {
+        Network::TESTNET
+    } else {
+        return Err(errors::DesoError::PublicKeyError(String::from(
+            "Unknown network prefix",
+        )));
+    };
+    Ok((body[3..].to_vec(), network))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected signatures computed independently with RFC 6979 nonces and low S
+    const POST_TXN: &str = "0000051c00000d7b22426f6479223a226869227d00008080a8b1e39fe7cb170021022b6c5a8e3d9f4b1a710ce2553890d46f13a7bb04c8295e66f09a3c8147d21e730203417070096465736f2d72757374086e66745f7479706506415554484f520001a801e0a71207";
+    const POST_SEED: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const SIGNED_POST_TXN: &str = "0000051c00000d7b22426f6479223a226869227d00008080a8b1e39fe7cb170021022b6c5a8e3d9f4b1a710ce2553890d46f13a7bb04c8295e66f09a3c8147d21e730203417070096465736f2d72757374086e66745f7479706506415554484f5246304402202aa80f75c834c0db89423fe56900cb38bc55aabd2f1e8df0ce0a3a6aa291e6c502203927d93030a4a1fc350c22b7cd9166e056c4a14dd22b56fceba2cac79dfce75b01a801e0a71207";
+
+    const TRANSFER_TXN: &str =
+        "0000020021031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f0000";
+    const TRANSFER_SEED: &str = "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35";
+    const SIGNED_TRANSFER_TXN: &str = "0000020021031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f00473045022100bbebac1927600588a84443ce4bd5f0e6dcb1807ed57a5d631c59dee211c1783d02200557c689fda3246ab3d470c477940c0a9c72fd6c4ad1bd04007d06eb54ae2364";
+
+    /// Signs an unsigned transaction hex the way the default signer does
+    fn sign(
+        tx: String,
+        private_key: &PrivateKey,
+        signature_index: usize,
+    ) -> Result<String, errors::DesoError> {
+        let transaction_bytes = match hex::decode(tx) {
+            Ok(b) => b,
+            Err(e) => return Err(errors::DesoError::SigningError(e.to_string())),
+        };
+        check_unsigned(&transaction_bytes, signature_index)?;
+        let signature = sign_bytes(&transaction_bytes, private_key);
+        insert_signature(&transaction_bytes, signature_index, &signature)
+    }
+
+    #[test]
+    fn test_sign_vectors() {
+        let post_key = PrivateKey::from_hex(POST_SEED).unwrap();
+        let signed = sign(POST_TXN.to_string(), &post_key, 97).unwrap();
+        assert_eq!(signed, SIGNED_POST_TXN);
+
+        let transfer_key = PrivateKey::from_hex(TRANSFER_SEED).unwrap();
+        let signed = sign(TRANSFER_TXN.to_string(), &transfer_key, 39).unwrap();
+        assert_eq!(signed, SIGNED_TRANSFER_TXN);
+    }
+
+    #[test]
+    fn test_verify_vectors() {
+        let post_signer =
+            hex::decode("031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f")
+                .unwrap();
+        let transfer_signer =
+            hex::decode("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2")
+                .unwrap();
+        let post = txn_lib::verify_signed_transaction(SIGNED_POST_TXN, Some(&post_signer)).unwrap();
+        assert_eq!(post.to_hex().unwrap(), SIGNED_POST_TXN);
+        assert!(
+            txn_lib::verify_signed_transaction(SIGNED_POST_TXN, Some(&transfer_signer)).is_err()
+        );
+        assert!(
+            txn_lib::verify_signed_transaction(SIGNED_TRANSFER_TXN, Some(&transfer_signer)).is_ok()
+        );
+        // The transfer was signed by a key other than its transactor
+        assert!(txn_lib::verify_signed_transaction(SIGNED_TRANSFER_TXN, None).is_err());
+        assert!(txn_lib::verify_signed_transaction(TRANSFER_TXN, Some(&transfer_signer)).is_err());
+    }
+
+    #[test]
+    fn test_sign_errors() {
+        // The zero key and the curve order are not valid secret keys
+        for seed in [
+            "not hex",
+            "00",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        ] {
+            assert!(matches!(
+                PrivateKey::from_hex(seed),
+                Err(errors::DesoError::PrivateKeyError(_))
+            ));
+        }
+
+        let key = PrivateKey::from_hex(POST_SEED).unwrap();
+        let cases = [
+            ("not hex", 97),
+            // Index past the end, and index not at an empty signature
+            (POST_TXN, 105),
+            (POST_TXN, usize::MAX),
+            (POST_TXN, 2),
+            (SIGNED_POST_TXN, 97),
+        ];
+        for (tx, index) in cases {
+            assert!(matches!(
+                sign(tx.to_string(), &key, index),
+                Err(errors::DesoError::SigningError(_))
+            ));
+        }
 
-    Ok(signed_txn_hex)
+        // Whatever a signer hands back has to look like a DER signature
+        let unsigned = hex::decode(POST_TXN).unwrap();
+        for signature in [vec![], vec![0x30; MAX_DER_SIGNATURE_LEN + 1]] {
+            assert!(matches!(
+                insert_signature(&unsigned, 97, &signature),
+                Err(errors::DesoError::SigningError(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,745 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto_lib;
+use crate::errors;
+use crate::key_lib::{PrivateKey, PublicKey};
+use crate::txn_lib::{
+    encode_byte_array, encode_uvarint, is_known_txn_type, HASH_LEN, PUBLIC_KEY_LEN,
+};
+use crate::Network;
+
+/// A freshly generated derived key. Authorize its public key with the owner
+/// account, then sign with `private_key`.
+#[derive(Debug, Clone)]
+pub struct DerivedKeyPair {
+    /// The derived public key
+    pub public_key: PublicKey,
+    /// The derived private key
+    pub private_key: PrivateKey,
+}
+
+/// Generates a random derived key for `network`
+pub fn generate_derived_key(network: Network) -> DerivedKeyPair {
+    let private_key = PrivateKey::generate();
+    DerivedKeyPair {
+        public_key: private_key.public_key(network),
+        private_key,
+    }
+}
+
+/// Creator coin operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CreatorCoinLimitOperation {
+    Any = 0,
+    Buy = 1,
+    Sell = 2,
+    Transfer = 3,
+}
+
+/// DAO coin operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DAOCoinLimitOperation {
+    Any = 0,
+    Mint = 1,
+    Burn = 2,
+    DisableMinting = 3,
+    UpdateTransferRestrictionStatus = 4,
+    Transfer = 5,
+}
+
+/// NFT operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NFTLimitOperation {
+    Any = 0,
+    Update = 1,
+    AcceptBid = 2,
+    AcceptTransfer = 3,
+    Burn = 4,
+    Bid = 5,
+    Transfer = 6,
+}
+
+/// Whether an association is between users or from a user to a post
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssociationClass {
+    User = 0,
+    Post = 1,
+}
+
+/// Association operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssociationOperation {
+    Any = 0,
+    Create = 1,
+    Delete = 2,
+}
+
+/// Access group operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessGroupOperation {
+    Any = 1,
+    Create = 2,
+    Update = 3,
+}
+
+/// Access group member operations a derived key can be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessGroupMemberOperation {
+    Any = 1,
+    Add = 2,
+    Remove = 3,
+    Update = 4,
+}
+
+/// The longest access group key name, in bytes
+pub const MAX_ACCESS_GROUP_KEY_NAME_LEN: usize = 32;
+
+/// The node's zero PKID, standing for any creator or app
+const ANY_PKID: [u8; PUBLIC_KEY_LEN] = [0; PUBLIC_KEY_LEN];
+
+/// A creator's PKID, which is their public key unless they swapped identities
+fn encode_pkid(creator: &Option<PublicKey>, data: &mut Vec<u8>) {
+    match creator {
+        Some(key) => data.extend_from_slice(key.as_bytes()),
+        None => data.extend_from_slice(&ANY_PKID),
+    }
+}
+
+/// Scope type of association and access group limits: any, or one in particular
+fn encode_scope<T>(scope: &Option<T>, data: &mut Vec<u8>) {
+    encode_uvarint(scope.is_some() as u64, data);
+}
+
+/// Access group key names are stored zero padded to their max length
+fn encode_key_name(key_name: &Option<String>, data: &mut Vec<u8>) {
+    let mut name = [0u8; MAX_ACCESS_GROUP_KEY_NAME_LEN];
+    if let Some(key_name) = key_name {
+        name[..key_name.len()].copy_from_slice(key_name.as_bytes());
+    }
+    encode_byte_array(&name, data);
+}
+
+/// Limits creator coin operations on `creator`'s coin, or any coin when `None`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreatorCoinLimitKey {
+    pub creator: Option<PublicKey>,
+    pub operation: CreatorCoinLimitOperation,
+}
+
+/// Limits DAO coin operations on `creator`'s coin, or any coin when `None`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DAOCoinLimitKey {
+    pub creator: Option<PublicKey>,
+    pub operation: DAOCoinLimitOperation,
+}
+
+/// Limits NFT operations on one serial number of the NFT at `post_hash`.
+/// `None` stands for any NFT, and serial number 0 for any serial number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NFTLimitKey {
+    pub post_hash: Option<[u8; HASH_LEN]>,
+    pub serial_number: u64,
+    pub operation: NFTLimitOperation,
+}
+
+/// Limits associations of `association_type` made through `app`, or through
+/// any app when `None`. An empty association type stands for any type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssociationLimitKey {
+    pub app: Option<PublicKey>,
+    pub association_type: String,
+    pub class: AssociationClass,
+    pub operation: AssociationOperation,
+}
+
+/// Limits operations on `owner`'s access group named `key_name`, or on any
+/// of their groups when `None`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessGroupLimitKey {
+    pub owner: PublicKey,
+    pub key_name: Option<String>,
+    pub operation: AccessGroupOperation,
+}
+
+/// Limits member operations on `owner`'s access group named `key_name`, or
+/// on any of their groups when `None`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessGroupMemberLimitKey {
+    pub owner: PublicKey,
+    pub key_name: Option<String>,
+    pub operation: AccessGroupMemberOperation,
+}
+
+impl CreatorCoinLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        encode_pkid(&self.creator, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+impl DAOCoinLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        encode_pkid(&self.creator, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+impl NFTLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = self.post_hash.unwrap_or([0; HASH_LEN]).to_vec();
+        encode_uvarint(self.serial_number, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+impl AssociationLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        encode_pkid(&self.app, &mut data);
+        encode_byte_array(self.association_type.as_bytes(), &mut data);
+        encode_uvarint(self.class as u64, &mut data);
+        encode_scope(&self.app, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+impl AccessGroupLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = self.owner.as_bytes().to_vec();
+        encode_scope(&self.key_name, &mut data);
+        encode_key_name(&self.key_name, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+impl AccessGroupMemberLimitKey {
+    fn encode(&self) -> Vec<u8> {
+        let mut data = self.owner.as_bytes().to_vec();
+        encode_scope(&self.key_name, &mut data);
+        encode_key_name(&self.key_name, &mut data);
+        encode_uvarint(self.operation as u64, &mut data);
+        data
+    }
+}
+
+/// Writes a limit map the way the node does: its length, then each encoded
+/// key and count, ordered by the encoded key
+fn encode_limit_map<K>(limits: &HashMap<K, u64>, encode: fn(&K) -> Vec<u8>, data: &mut Vec<u8>) {
+    let sorted: BTreeMap<Vec<u8>, u64> = limits.iter().map(|(k, v)| (encode(k), *v)).collect();
+    encode_uvarint(sorted.len() as u64, data);
+    for (key, count) in sorted {
+        data.extend_from_slice(&key);
+        encode_uvarint(count, data);
+    }
+}
+
+/// What a derived key is allowed to spend and do. Build one with
+/// `TransactionSpendingLimitBuilder`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionSpendingLimit {
+    /// Total DESO, in nanos, the key may spend across all transactions
+    pub global_deso_limit: u64,
+    /// How many transactions of each type the key may sign, by txn type
+    pub txn_count_limits: BTreeMap<u64, u64>,
+    /// How many creator coin operations the key may sign
+    pub creator_coin_limits: HashMap<CreatorCoinLimitKey, u64>,
+    /// How many DAO coin operations the key may sign
+    pub dao_coin_limits: HashMap<DAOCoinLimitKey, u64>,
+    /// How many NFT operations the key may sign
+    pub nft_limits: HashMap<NFTLimitKey, u64>,
+    /// How many association operations the key may sign
+    pub association_limits: HashMap<AssociationLimitKey, u64>,
+    /// How many access group operations the key may sign
+    pub access_group_limits: HashMap<AccessGroupLimitKey, u64>,
+    /// How many access group member operations the key may sign
+    pub access_group_member_limits: HashMap<AccessGroupMemberLimitKey, u64>,
+    /// Lift every limit, letting the key sign anything the owner could
+    pub is_unlimited: bool,
+}
+
+impl TransactionSpendingLimit {
+    /// A limit that lets the key sign anything the owner could
+    pub fn unlimited() -> Self {
+        TransactionSpendingLimit {
+            is_unlimited: true,
+            ..Default::default()
+        }
+    }
+
+    /// The binary encoding the node expects and the access signature covers
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        encode_uvarint(self.global_deso_limit, &mut data);
+        encode_uvarint(self.txn_count_limits.len() as u64, &mut data);
+        for (txn_type, count) in &self.txn_count_limits {
+            encode_uvarint(*txn_type, &mut data);
+            encode_uvarint(*count, &mut data);
+        }
+        encode_limit_map(
+            &self.creator_coin_limits,
+            CreatorCoinLimitKey::encode,
+            &mut data,
+        );
+        encode_limit_map(&self.dao_coin_limits, DAOCoinLimitKey::encode, &mut data);
+        encode_limit_map(&self.nft_limits, NFTLimitKey::encode, &mut data);
+        // DAO coin limit orders
+        encode_uvarint(0, &mut data);
+        data.push(self.is_unlimited as u8);
+        encode_limit_map(
+            &self.association_limits,
+            AssociationLimitKey::encode,
+            &mut data,
+        );
+        encode_limit_map(
+            &self.access_group_limits,
+            AccessGroupLimitKey::encode,
+            &mut data,
+        );
+        encode_limit_map(
+            &self.access_group_member_limits,
+            AccessGroupMemberLimitKey::encode,
+            &mut data,
+        );
+        // Stake, unstake, unlock stake and lockup limits
+        for _ in 0..4 {
+            encode_uvarint(0, &mut data);
+        }
+        data
+    }
+}
+
+/// Builder for a `TransactionSpendingLimit`
+#[derive(Debug, Default)]
+pub struct TransactionSpendingLimitBuilder {
+    limit: TransactionSpendingLimit,
+}
+
+impl TransactionSpendingLimitBuilder {
+    pub fn new() -> Self {
+        TransactionSpendingLimitBuilder::default()
+    }
+    /// Total DESO, in nanos, the key may spend across all transactions
+    pub fn global_deso_limit(mut self, nanos: u64) -> Self {
+        self.limit.global_deso_limit = nanos;
+        self
+    }
+    /// How many transactions of `txn_type`, one of the `TXN_TYPE_*` codes,
+    /// the key may sign
+    pub fn txn_count(mut self, txn_type: u64, count: u64) -> Self {
+        self.limit.txn_count_limits.insert(txn_type, count);
+        self
+    }
+    /// How many creator coin `operation`s on `creator`'s coin (any coin when
+    /// `None`) the key may sign
+    pub fn creator_coin(
+        mut self,
+        creator: Option<PublicKey>,
+        operation: CreatorCoinLimitOperation,
+        count: u64,
+    ) -> Self {
+        let key = CreatorCoinLimitKey { creator, operation };
+        self.limit.creator_coin_limits.insert(key, count);
+        self
+    }
+    /// How many DAO coin `operation`s on `creator`'s coin (any coin when
+    /// `None`) the key may sign
+    pub fn dao_coin(
+        mut self,
+        creator: Option<PublicKey>,
+        operation: DAOCoinLimitOperation,
+        count: u64,
+    ) -> Self {
+        let key = DAOCoinLimitKey { creator, operation };
+        self.limit.dao_coin_limits.insert(key, count);
+        self
+    }
+    /// How many NFT `operation`s on the NFT at `post_hash` (any NFT when
+    /// `None`) and `serial_number` (any when 0) the key may sign
+    pub fn nft(
+        mut self,
+        post_hash: Option<[u8; HASH_LEN]>,
+        serial_number: u64,
+        operation: NFTLimitOperation,
+        count: u64,
+    ) -> Self {
+        let key = NFTLimitKey {
+            post_hash,
+            serial_number,
+            operation,
+        };
+        self.limit.nft_limits.insert(key, count);
+        self
+    }
+    /// How many association `operation`s of `association_type` (any type when
+    /// empty) through `app` (any app when `None`) the key may sign
+    pub fn association(
+        mut self,
+        app: Option<PublicKey>,
+        association_type: &str,
+        class: AssociationClass,
+        operation: AssociationOperation,
+        count: u64,
+    ) -> Self {
+        let key = AssociationLimitKey {
+            app,
+            association_type: association_type.to_string(),
+            class,
+            operation,
+        };
+        self.limit.association_limits.insert(key, count);
+        self
+    }
+    /// How many `operation`s on `owner`'s access group `key_name` (any of
+    /// their groups when `None`) the key may sign
+    pub fn access_group(
+        mut self,
+        owner: PublicKey,
+        key_name: Option<&str>,
+        operation: AccessGroupOperation,
+        count: u64,
+    ) -> Self {
+        let key = AccessGroupLimitKey {
+            owner,
+            key_name: key_name.map(String::from),
+            operation,
+        };
+        self.limit.access_group_limits.insert(key, count);
+        self
+    }
+    /// How many member `operation`s on `owner`'s access group `key_name` (any
+    /// of their groups when `None`) the key may sign
+    pub fn access_group_member(
+        mut self,
+        owner: PublicKey,
+        key_name: Option<&str>,
+        operation: AccessGroupMemberOperation,
+        count: u64,
+    ) -> Self {
+        let key = AccessGroupMemberLimitKey {
+            owner,
+            key_name: key_name.map(String::from),
+            operation,
+        };
+        self.limit.access_group_member_limits.insert(key, count);
+        self
+    }
+    /// Lift every limit, letting the key sign anything the owner could
+    pub fn is_unlimited(mut self, is_unlimited: bool) -> Self {
+        self.limit.is_unlimited = is_unlimited;
+        self
+    }
+    /// Builds the TransactionSpendingLimit
+    pub fn build(self) -> Result<TransactionSpendingLimit, errors::DesoError> {
+        for txn_type in self.limit.txn_count_limits.keys() {
+            if !is_known_txn_type(*txn_type) {
+                return Err(errors::DesoError::SpendingLimitError(format!(
+                    "Unknown transaction type {}",
+                    txn_type
+                )));
+            }
+        }
+        let key_names = self
+            .limit
+            .access_group_limits
+            .keys()
+            .map(|k| &k.key_name)
+            .chain(
+                self.limit
+                    .access_group_member_limits
+                    .keys()
+                    .map(|k| &k.key_name),
+            );
+        for key_name in key_names.flatten() {
+            if key_name.is_empty() || key_name.len() > MAX_ACCESS_GROUP_KEY_NAME_LEN {
+                return Err(errors::DesoError::SpendingLimitError(format!(
+                    "Access group key name must be 1 to {} bytes: {}",
+                    MAX_ACCESS_GROUP_KEY_NAME_LEN, key_name
+                )));
+            }
+        }
+        Ok(self.limit)
+    }
+}
+
+/// The owner's signature letting `derived_public_key` sign for it until
+/// `expiration_block` within `spending_limit`: a signature over the double
+/// sha256 of the derived key, big endian expiration block and spending limit
+pub(crate) fn access_signature(
+    owner_key: &PrivateKey,
+    derived_public_key: &PublicKey,
+    expiration_block: u64,
+    spending_limit: &TransactionSpendingLimit,
+) -> Vec<u8> {
+    let mut access_bytes = derived_public_key.as_bytes().to_vec();
+    access_bytes.extend_from_slice(&expiration_block.to_be_bytes());
+    access_bytes.extend_from_slice(&spending_limit.to_bytes());
+    crypto_lib::sign_bytes(&access_bytes, owner_key)
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct AuthorizeDerivedKeyRequest {
+    #[serde(rename = "OwnerPublicKeyBase58Check")]
+    pub owner_public_key: PublicKey,
+    #[serde(rename = "DerivedPublicKeyBase58Check")]
+    pub derived_public_key: PublicKey,
+    #[serde(rename = "ExpirationBlock")]
+    pub expiration_block: u64,
+    #[serde(rename = "AccessSignature")]
+    pub access_signature: String,
+    #[serde(rename = "DeleteKey")]
+    pub delete_key: bool,
+    #[serde(rename = "DerivedKeySignature")]
+    pub derived_key_signature: bool,
+    #[serde(rename = "TransactionSpendingLimitHex")]
+    pub transaction_spending_limit_hex: String,
+    #[serde(rename = "MinFeeRateNanosPerKB")]
+    pub fee_rate: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct GetUserDerivedKeysRequest {
+    #[serde(rename = "PublicKeyBase58Check")]
+    pub public_key: PublicKey,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct GetUserDerivedKeysResponse {
+    #[serde(rename = "DerivedKeys")]
+    pub derived_keys: Option<HashMap<String, DerivedKeyEntry>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct GetSingleDerivedKeyResponse {
+    #[serde(rename = "DerivedKey")]
+    pub derived_key: Option<DerivedKeyEntry>,
+}
+
+/// A derived key the node knows about
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DerivedKeyEntry {
+    #[serde(rename = "OwnerPublicKeyBase58Check")]
+    pub owner_public_key: PublicKey,
+    #[serde(rename = "DerivedPublicKeyBase58Check")]
+    pub derived_public_key: PublicKey,
+    /// The key can't sign transactions in or after this block
+    #[serde(rename = "ExpirationBlock")]
+    pub expiration_block: u64,
+    /// Whether the key is authorized, false once it has been revoked
+    #[serde(rename = "IsValid")]
+    pub is_active: bool,
+    #[serde(rename = "ExtraData", default)]
+    pub extra_data: Option<HashMap<String, String>>,
+    /// What the key has left to spend
+    #[serde(rename = "TransactionSpendingLimit", default)]
+    pub remaining_limit: Option<RemainingSpendingLimit>,
+}
+
+/// What a derived key has left to spend, as reported by the node
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RemainingSpendingLimit {
+    /// DESO, in nanos, the key can still spend
+    #[serde(rename = "GlobalDESOLimit", default)]
+    pub global_deso_limit: u64,
+    /// Transactions the key can still sign, by txn type name (e.g. `SUBMIT_POST`)
+    #[serde(rename = "TransactionCountLimitMap", default)]
+    pub txn_count_limits: Option<HashMap<String, u64>>,
+    #[serde(rename = "IsUnlimited", default)]
+    pub is_unlimited: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txn_lib::{
+        TXN_TYPE_ATOMIC_TXNS_WRAPPER, TXN_TYPE_BLOCK_REWARD, TXN_TYPE_FOLLOW, TXN_TYPE_LIKE,
+        TXN_TYPE_NFT_BID, TXN_TYPE_SUBMIT_POST, TXN_TYPE_UPDATE_BITCOIN_USD_EXCHANGE_RATE,
+        TXN_TYPE_UPDATE_PROFILE,
+    };
+
+    #[test]
+    fn test_spending_limit_bytes() {
+        assert_eq!(
+            hex::encode(TransactionSpendingLimit::default().to_bytes()),
+            "0000000000000000000000000000"
+        );
+        assert_eq!(
+            hex::encode(TransactionSpendingLimit::unlimited().to_bytes()),
+            "0000000000000100000000000000"
+        );
+
+        let mut limit = TransactionSpendingLimit {
+            global_deso_limit: 1_000_000_000,
+            ..Default::default()
+        };
+        limit.txn_count_limits.insert(TXN_TYPE_SUBMIT_POST, 300);
+        limit.txn_count_limits.insert(2, 1);
+        // 1e9 nanos, then {2: 1, 5: 300} in type order
+        assert_eq!(
+            hex::encode(limit.to_bytes()),
+            "8094ebdc0302020105ac02000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_spending_limit_builder_bytes() {
+        let creator = PrivateKey::from_hex(&"01".repeat(32))
+            .unwrap()
+            .public_key(Network::MAINNET);
+        let creator_hex = hex::encode(creator.as_bytes());
+        let any = "00".repeat(PUBLIC_KEY_LEN);
+        let limit = TransactionSpendingLimitBuilder::new()
+            .global_deso_limit(100)
+            .txn_count(TXN_TYPE_SUBMIT_POST, 2)
+            .creator_coin(Some(creator.clone()), CreatorCoinLimitOperation::Sell, 1)
+            .creator_coin(None, CreatorCoinLimitOperation::Any, 3)
+            .dao_coin(None, DAOCoinLimitOperation::Transfer, 4)
+            .nft(Some([7; HASH_LEN]), 0, NFTLimitOperation::Bid, 5)
+            .association(
+                Some(creator.clone()),
+                "ENDORSEMENT",
+                AssociationClass::User,
+                AssociationOperation::Create,
+                6,
+            )
+            .access_group(
+                creator.clone(),
+                Some("friends"),
+                AccessGroupOperation::Any,
+                7,
+            )
+            .access_group_member(creator.clone(), None, AccessGroupMemberOperation::Add, 8)
+            .build()
+            .unwrap();
+
+        let key_name = format!("20{}{}", hex::encode("friends"), "00".repeat(25));
+        let expected = [
+            // Global limit and {SubmitPost: 2}
+            String::from("64010502"),
+            // Creator coins, the zero any creator PKID sorting first
+            format!("02{}0003{}0201", any, creator_hex),
+            // DAO coins
+            format!("01{}0504", any),
+            // NFTs
+            format!("01{}000505", "07".repeat(HASH_LEN)),
+            // No limit orders, not unlimited
+            String::from("0000"),
+            // Associations, scoped to the app
+            format!("01{}0b{}00010106", creator_hex, hex::encode("ENDORSEMENT")),
+            // Access groups, one scoped and one for any group
+            format!("01{}01{}0107", creator_hex, key_name),
+            format!("01{}00{}{}0208", creator_hex, "20", "00".repeat(32)),
+            // No stake, unstake, unlock stake or lockup limits
+            String::from("00000000"),
+        ]
+        .concat();
+        assert_eq!(hex::encode(limit.to_bytes()), expected);
+    }
+
+    #[test]
+    #[ignore = "needs a spending limit encoded by a node, see testdata/README.md"]
+    fn test_spending_limit_bytes_match_node() {
+        // The limit in testdata/spending_limits/get_access_bytes.json
+        let limit = TransactionSpendingLimitBuilder::new()
+            .global_deso_limit(100)
+            .txn_count(TXN_TYPE_SUBMIT_POST, 2)
+            .txn_count(TXN_TYPE_FOLLOW, 3)
+            .txn_count(TXN_TYPE_LIKE, 300)
+            .creator_coin(None, CreatorCoinLimitOperation::Any, 3)
+            .dao_coin(None, DAOCoinLimitOperation::Transfer, 4)
+            .nft(Some([7; HASH_LEN]), 0, NFTLimitOperation::Bid, 5)
+            .build()
+            .unwrap();
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/spending_limits/limit.hex");
+        let node_hex = match std::fs::read_to_string(&path) {
+            Ok(hex) => hex.trim().to_string(),
+            Err(e) => panic!("{}: {}, see testdata/README.md", path.display(), e),
+        };
+        assert_eq!(hex::encode(limit.to_bytes()), node_hex);
+    }
+
+    #[test]
+    fn test_spending_limit_builder_checks_txn_types() {
+        let mut builder = TransactionSpendingLimitBuilder::new();
+        for txn_type in [
+            TXN_TYPE_BLOCK_REWARD,
+            TXN_TYPE_SUBMIT_POST,
+            TXN_TYPE_UPDATE_PROFILE,
+            TXN_TYPE_UPDATE_BITCOIN_USD_EXCHANGE_RATE,
+            TXN_TYPE_FOLLOW,
+            TXN_TYPE_NFT_BID,
+            TXN_TYPE_ATOMIC_TXNS_WRAPPER,
+        ] {
+            builder = builder.txn_count(txn_type, 1);
+        }
+        assert_eq!(builder.build().unwrap().txn_count_limits.len(), 7);
+        for txn_type in [0, 7, TXN_TYPE_ATOMIC_TXNS_WRAPPER + 1, u64::MAX] {
+            assert!(matches!(
+                TransactionSpendingLimitBuilder::new()
+                    .txn_count(TXN_TYPE_SUBMIT_POST, 1)
+                    .txn_count(txn_type, 1)
+                    .build(),
+                Err(errors::DesoError::SpendingLimitError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_spending_limit_builder_checks_key_names() {
+        let owner = generate_derived_key(Network::MAINNET).public_key;
+        for key_name in ["", &"a".repeat(MAX_ACCESS_GROUP_KEY_NAME_LEN + 1)] {
+            assert!(matches!(
+                TransactionSpendingLimitBuilder::new()
+                    .access_group(
+                        owner.clone(),
+                        Some(key_name),
+                        AccessGroupOperation::Create,
+                        1
+                    )
+                    .build(),
+                Err(errors::DesoError::SpendingLimitError(_))
+            ));
+        }
+        assert!(TransactionSpendingLimitBuilder::new()
+            .access_group_member(
+                owner,
+                Some(&"a".repeat(MAX_ACCESS_GROUP_KEY_NAME_LEN)),
+                AccessGroupMemberOperation::Remove,
+                1
+            )
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_access_signature() {
+        let owner_key = PrivateKey::from_hex(&"01".repeat(32)).unwrap();
+        let owner = owner_key.public_key(Network::MAINNET);
+        let derived = generate_derived_key(Network::MAINNET);
+        assert_eq!(
+            derived.private_key.public_key(Network::MAINNET),
+            derived.public_key
+        );
+
+        let limit = TransactionSpendingLimit::unlimited();
+        let signature = access_signature(&owner_key, &derived.public_key, 1000, &limit);
+
+        let mut access_bytes = derived.public_key.as_bytes().to_vec();
+        access_bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0x03, 0xe8]);
+        access_bytes.extend_from_slice(&limit.to_bytes());
+        assert!(crypto_lib::verify_bytes(&access_bytes, &signature, owner.as_bytes()).is_ok());
+        assert!(
+            crypto_lib::verify_bytes(&access_bytes, &signature, derived.public_key.as_bytes())
+                .is_err()
+        );
+    }
+}
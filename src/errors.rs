@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum DesoError {
     #[error("Missing `{0}`")]
@@ -20,6 +21,38 @@ pub enum DesoError {
     PaymentError(String),
     #[error("Get Profile Error: `{0}`")]
     ProfileRequestError(String),
-    #[error("Problem Getting Index: `{0}`")]
+    #[error("Problem Signing Transaction: `{0}`")]
     SigningError(String),
+    #[error("Invalid Node Config: `{0}`")]
+    NodeConfigError(String),
+    #[error("Invalid Submit Policy: `{0}`")]
+    SubmitPolicyError(String),
+    #[error("Problem Encoding Transaction: `{0}`")]
+    TxnEncodingError(String),
+    #[error("Problem Decoding Transaction: `{0}`")]
+    TxnDecodingError(String),
+    #[error("Transaction Does Not Match Request: `{0}`")]
+    TxnVerificationError(String),
+    #[error("Invalid Public Key: `{0}`")]
+    PublicKeyError(String),
+    #[error("Invalid Private Key: `{0}`")]
+    PrivateKeyError(String),
+    #[error("Signature Verification Failed: `{0}`")]
+    VerificationError(String),
+    #[error("Invalid Mnemonic: `{0}`")]
+    MnemonicError(String),
+    #[error("Invalid Spending Limit: `{0}`")]
+    SpendingLimitError(String),
+    #[error("Keystore Error: `{0}`")]
+    KeystoreError(String),
+    #[error("Invalid Post Hash: `{0}`")]
+    PostHashError(String),
+    #[error("Cannot Edit Post: `{0}`")]
+    PostEditError(String),
+    #[error("Invalid Post Data: `{0}`")]
+    InvalidPostData(String),
+    #[error("Key Mismatch: `{0}`")]
+    KeyMismatch(String),
+    #[error("Signer Mismatch: `{0}`")]
+    SignerMismatch(String),
 }
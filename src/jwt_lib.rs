@@ -0,0 +1,182 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bitcoin_hashes::sha256;
+use secp256k1::{Message, Secp256k1};
+use serde::{Deserialize, Serialize};
+
+use crate::errors;
+use crate::key_lib::{PrivateKey, PublicKey};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct JwtHeader {
+    alg: String,
+    typ: String,
+}
+
+/// The claims Deso nodes read from an authentication JWT
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JwtClaims {
+    pub iat: u64,
+    pub exp: u64,
+    #[serde(
+        rename = "derivedPublicKeyBase58Check",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub derived_public_key: Option<PublicKey>,
+}
+
+fn encode_part<T: Serialize>(part: &T, what: &str) -> Result<String, errors::DesoError> {
+    match serde_json::to_vec(part) {
+        Ok(j) => Ok(URL_SAFE_NO_PAD.encode(j)),
+        Err(e) => Err(errors::DesoError::JsonError(
+            String::from(what),
+            e.to_string(),
+        )),
+    }
+}
+
+/// Makes an ES256 JWT signed with `private_key`, valid for `expiry` from now.
+///
+/// Deso signs JWTs over secp256k1 rather than P-256: the signature is the
+/// 64 byte r || s of the sha256 of the header and claims.
+pub(crate) fn sign_jwt(
+    private_key: &PrivateKey,
+    derived_public_key: Option<&PublicKey>,
+    expiry: Duration,
+) -> Result<String, errors::DesoError> {
+    if expiry.is_zero() {
+        return Err(errors::DesoError::SigningError(String::from(
+            "JWT expiry must be longer than zero",
+        )));
+    }
+    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(e) => return Err(errors::DesoError::SigningError(e.to_string())),
+    };
+    // Round up so a sub second expiry still gives a usable token
+    let exp = match now
+        .checked_add(expiry.as_secs())
+        .and_then(|exp| exp.checked_add(u64::from(expiry.subsec_nanos() > 0)))
+    {
+        Some(exp) => exp,
+        None => {
+            return Err(errors::DesoError::SigningError(format!(
+                "JWT expiry {:?} is too far in the future",
+                expiry
+            )))
+        }
+    };
+    let claims = JwtClaims {
+        iat: now,
+        exp,
+        derived_public_key: derived_public_key.cloned(),
+    };
+    sign_claims(private_key, &claims)
+}
+
+pub(crate) fn sign_claims(
+    private_key: &PrivateKey,
+    claims: &JwtClaims,
+) -> Result<String, errors::DesoError> {
+    let header = JwtHeader {
+        alg: String::from("ES256"),
+        typ: String::from("JWT"),
+    };
+    let signing_input = format!(
+        "{}.{}",
+        encode_part(&header, "JWT HEADER")?,
+        encode_part(claims, "JWT CLAIMS")?
+    );
+    let message = Message::from_hashed_data::<sha256::Hash>(signing_input.as_bytes());
+    let signature = Secp256k1::signing_only().sign_ecdsa(&message, &private_key.secret_key());
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature.serialize_compact())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Network;
+    use secp256k1::ecdsa::Signature;
+
+    const SEED_HEX: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn verify(token: &str, public_key: &PublicKey) -> JwtClaims {
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let header: JwtHeader =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[0]).unwrap()).unwrap();
+        assert_eq!(header.alg, "ES256");
+        assert_eq!(header.typ, "JWT");
+
+        let signing_input = format!("{}.{}", parts[0], parts[1]);
+        let message = Message::from_hashed_data::<sha256::Hash>(signing_input.as_bytes());
+        let signature =
+            Signature::from_compact(&URL_SAFE_NO_PAD.decode(parts[2]).unwrap()).unwrap();
+        Secp256k1::verification_only()
+            .verify_ecdsa(&message, &signature, &public_key.to_secp256k1())
+            .unwrap();
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_owner_and_derived_jwt() {
+        let owner = PrivateKey::from_hex(SEED_HEX).unwrap();
+        let token = sign_jwt(&owner, None, Duration::from_secs(600)).unwrap();
+        let claims = verify(&token, &owner.public_key(Network::MAINNET));
+        assert_eq!(claims.exp - claims.iat, 600);
+        assert_eq!(claims.derived_public_key, None);
+        assert!(!token.contains('='));
+        let payload = URL_SAFE_NO_PAD
+            .decode(token.split('.').nth(1).unwrap())
+            .unwrap();
+        assert!(!String::from_utf8(payload)
+            .unwrap()
+            .contains("derivedPublicKeyBase58Check"));
+
+        let derived = PrivateKey::generate();
+        let derived_public_key = derived.public_key(Network::MAINNET);
+        let token = sign_jwt(
+            &derived,
+            Some(&derived_public_key),
+            Duration::from_millis(1500),
+        )
+        .unwrap();
+        let claims = verify(&token, &derived_public_key);
+        assert_eq!(claims.exp - claims.iat, 2);
+        assert_eq!(claims.derived_public_key, Some(derived_public_key));
+
+        assert!(matches!(
+            sign_jwt(&owner, None, Duration::ZERO),
+            Err(errors::DesoError::SigningError(_))
+        ));
+        assert!(matches!(
+            sign_jwt(&owner, None, Duration::MAX),
+            Err(errors::DesoError::SigningError(_))
+        ));
+    }
+
+    #[test]
+    fn test_jwt_is_deterministic_for_claims() {
+        let owner = PrivateKey::from_hex(SEED_HEX).unwrap();
+        let claims = JwtClaims {
+            iat: 1_700_000_000,
+            exp: 1_700_000_600,
+            derived_public_key: None,
+        };
+        let token = sign_claims(&owner, &claims).unwrap();
+        assert_eq!(token, sign_claims(&owner, &claims).unwrap());
+        assert_eq!(
+            token.split('.').take(2).collect::<Vec<_>>(),
+            vec![
+                "eyJhbGciOiJFUzI1NiIsInR5cCI6IkpXVCJ9",
+                "eyJpYXQiOjE3MDAwMDAwMDAsImV4cCI6MTcwMDAwMDYwMH0"
+            ]
+        );
+    }
+}
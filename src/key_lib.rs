@@ -0,0 +1,406 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha2::Sha512;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroizing;
+
+use crate::crypto_lib;
+use crate::errors;
+use crate::txn_lib::PUBLIC_KEY_LEN;
+use crate::Network;
+
+/// A Deso public key: a compressed secp256k1 key along with the network it
+/// belongs to. Displays and serializes as Base58Check (`BC1YL...` on mainnet,
+/// `tBC...` on testnet).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PublicKey {
+    bytes: [u8; PUBLIC_KEY_LEN],
+    network: Network,
+}
+
+impl PublicKey {
+    /// Parses a Base58Check public key, checking its checksum, network prefix
+    /// and that it is a valid point on the curve
+    pub fn from_base58_check(encoded: &str) -> Result<Self, errors::DesoError> {
+        let (payload, network) = crypto_lib::base58_check_decode(encoded)?;
+        PublicKey::from_bytes(&payload, network)
+    }
+
+    /// A public key from its 33 byte compressed encoding
+    pub fn from_bytes(bytes: &[u8], network: Network) -> Result<Self, errors::DesoError> {
+        let key = match secp256k1::PublicKey::from_slice(bytes) {
+            Ok(k) => k,
+            Err(e) => return Err(errors::DesoError::PublicKeyError(e.to_string())),
+        };
+        Ok(PublicKey::from_secp256k1(&key, network))
+    }
+
+    /// A public key from a secp256k1 key
+    pub fn from_secp256k1(key: &secp256k1::PublicKey, network: Network) -> Self {
+        PublicKey {
+            bytes: key.serialize(),
+            network,
+        }
+    }
+
+    /// The public key of a secret key
+    pub(crate) fn from_secret_key(secret_key: &SecretKey, network: Network) -> Self {
+        PublicKey::from_secp256k1(
+            &secp256k1::PublicKey::from_secret_key_global(secret_key),
+            network,
+        )
+    }
+
+    /// The secp256k1 key
+    pub fn to_secp256k1(&self) -> secp256k1::PublicKey {
+        // The bytes were checked when the key was made
+        secp256k1::PublicKey::from_slice(&self.bytes).expect("valid compressed public key")
+    }
+
+    /// The 33 byte compressed encoding of the key
+    pub fn as_bytes(&self) -> &[u8; PUBLIC_KEY_LEN] {
+        &self.bytes
+    }
+
+    /// The network the key's Base58Check prefix belongs to
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The Base58Check encoding of the key
+    pub fn to_base58_check(&self) -> String {
+        crypto_lib::base58_check_encode(&self.bytes, self.network)
+    }
+
+    /// Errors unless the key belongs to `network`
+    pub(crate) fn check_network(&self, network: Network) -> Result<(), errors::DesoError> {
+        if self.network != network {
+            return Err(errors::DesoError::PublicKeyError(format!(
+                "{} is a {:?} key, expected {:?}",
+                self, self.network, network
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_base58_check())
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PublicKey({})", self)
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = errors::DesoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PublicKey::from_base58_check(s)
+    }
+}
+
+impl TryFrom<String> for PublicKey {
+    type Error = errors::DesoError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        PublicKey::from_base58_check(&s)
+    }
+}
+
+impl From<PublicKey> for String {
+    fn from(key: PublicKey) -> Self {
+        key.to_base58_check()
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_base58_check())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        PublicKey::from_base58_check(&encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A seed hex or derived private key.
+///
+/// Wiped from memory when dropped and never printed: Debug and Display show
+/// `PrivateKey(<redacted>)`. Only serializable with the `serialize-secrets`
+/// feature.
+#[derive(Clone)]
+pub struct PrivateKey {
+    bytes: Zeroizing<[u8; 32]>,
+}
+
+impl PrivateKey {
+    /// Parses a seed hex or derived private key
+    pub fn from_hex(seed_hex: &str) -> Result<Self, errors::DesoError> {
+        let bytes = match hex::decode(seed_hex) {
+            Ok(b) => Zeroizing::new(b),
+            Err(e) => {
+                return Err(errors::DesoError::PrivateKeyError(format!(
+                    "Problem decoding seed hex: {}",
+                    e
+                )))
+            }
+        };
+        let secret_key = match SecretKey::from_slice(&bytes) {
+            Ok(k) => k,
+            Err(e) => {
+                return Err(errors::DesoError::PrivateKeyError(format!(
+                    "Not a secp256k1 private key: {}",
+                    e
+                )))
+            }
+        };
+        Ok(PrivateKey::from_secret_key(&secret_key))
+    }
+
+    /// A new random key
+    pub fn generate() -> Self {
+        PrivateKey::from_secret_key(&SecretKey::new(&mut secp256k1::rand::thread_rng()))
+    }
+
+    pub(crate) fn from_secret_key(secret_key: &SecretKey) -> Self {
+        PrivateKey {
+            bytes: Zeroizing::new(secret_key.secret_bytes()),
+        }
+    }
+
+    /// The secp256k1 key, for signing
+    pub(crate) fn secret_key(&self) -> SecretKey {
+        // The bytes were checked when the key was made
+        SecretKey::from_slice(&*self.bytes).expect("valid private key")
+    }
+
+    /// The public key of this private key, on `network`
+    pub fn public_key(&self, network: Network) -> PublicKey {
+        PublicKey::from_secret_key(&self.secret_key(), network)
+    }
+
+    /// The key as hex, for exporting it. Handle with care.
+    pub fn expose_secret_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(*self.bytes))
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
+
+impl fmt::Display for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
+
+#[cfg(feature = "serialize-secrets")]
+impl Serialize for PrivateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.expose_secret_hex())
+    }
+}
+
+#[cfg(feature = "serialize-secrets")]
+impl<'de> Deserialize<'de> for PrivateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let seed_hex = Zeroizing::new(String::deserialize(deserializer)?);
+        PrivateKey::from_hex(&seed_hex).map_err(serde::de::Error::custom)
+    }
+}
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// The BIP44 path Deso wallets derive the owner key on: m/44'/0'/0'/0/0
+const DESO_DERIVATION_PATH: [u32; 5] = [HARDENED | 44, HARDENED, HARDENED, 0, 0];
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac = Hmac::new(Sha512::new(), key);
+    mac.input(data);
+    let mut out = [0u8; 64];
+    out.copy_from_slice(mac.result().code());
+    out
+}
+
+fn mnemonic_error<E: fmt::Display>(e: E) -> errors::DesoError {
+    errors::DesoError::MnemonicError(e.to_string())
+}
+
+/// The owner secret key of a BIP39 mnemonic and passphrase, derived on the
+/// path Deso wallets use
+pub(crate) fn private_key_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+) -> Result<PrivateKey, errors::DesoError> {
+    let mnemonic = bip39::Mnemonic::parse(mnemonic).map_err(mnemonic_error)?;
+    let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+
+    // BIP32 master key, then one private child derivation per path step
+    let master = Zeroizing::new(hmac_sha512(b"Bitcoin seed", &*seed));
+    let mut key = SecretKey::from_slice(&master[..32]).map_err(mnemonic_error)?;
+    let mut chain_code = Zeroizing::new(master[32..].to_vec());
+    for index in DESO_DERIVATION_PATH {
+        let mut data = Zeroizing::new(Vec::with_capacity(37));
+        if index & HARDENED != 0 {
+            data.push(0);
+            data.extend_from_slice(&key.secret_bytes());
+        } else {
+            data.extend_from_slice(&secp256k1::PublicKey::from_secret_key_global(&key).serialize());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+        let child = Zeroizing::new(hmac_sha512(&chain_code, &data));
+        key.add_assign(&child[..32]).map_err(mnemonic_error)?;
+        chain_code = Zeroizing::new(child[32..].to_vec());
+    }
+    Ok(PrivateKey::from_secret_key(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Public key of the seed 0101..01
+    const KEY_HEX: &str = "031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f";
+
+    #[test]
+    fn test_base58_check_round_trip() {
+        let bytes = hex::decode(KEY_HEX).unwrap();
+        for (network, prefix) in [(Network::MAINNET, "BC1YL"), (Network::TESTNET, "tBC")] {
+            let key = PublicKey::from_bytes(&bytes, network).unwrap();
+            let encoded = key.to_string();
+            assert!(encoded.starts_with(prefix), "{}", encoded);
+            let parsed: PublicKey = encoded.parse().unwrap();
+            assert_eq!(parsed, key);
+            assert_eq!(parsed.network(), network);
+            assert_eq!(parsed.as_bytes().to_vec(), bytes);
+            assert_eq!(
+                PublicKey::from_secp256k1(&parsed.to_secp256k1(), network),
+                key
+            );
+
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(json, format!("\"{}\"", encoded));
+            assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_keys() {
+        let bytes = hex::decode(KEY_HEX).unwrap();
+        let encoded = PublicKey::from_bytes(&bytes, Network::MAINNET)
+            .unwrap()
+            .to_string();
+
+        // Broken checksum
+        let mut tampered = bs58::decode(&encoded).into_vec().unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let tampered = bs58::encode(tampered).into_string();
+
+        // Right checksum, but not a point on the curve
+        let mut off_curve = bytes.clone();
+        off_curve[0] = 0x05;
+        let off_curve = crypto_lib::base58_check_encode(&off_curve, Network::MAINNET);
+
+        for bad in ["", "not base58 0OIl", &tampered, &off_curve] {
+            assert!(matches!(
+                PublicKey::from_base58_check(bad),
+                Err(errors::DesoError::PublicKeyError(_))
+            ));
+        }
+        assert!(serde_json::from_str::<PublicKey>(&format!("\"{}\"", tampered)).is_err());
+        assert!(PublicKey::from_bytes(&bytes[1..], Network::MAINNET).is_err());
+    }
+
+    #[test]
+    fn test_mnemonic_keys() {
+        // BIP39 test mnemonic, checked against the published BIP44 keys
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let cases = [
+            (
+                "",
+                "e284129cc0922579a535bbf4d1a3b25773090d28c909bc0fed73b5e0222cc372",
+                "03aaeb52dd7494c361049de67cc680e83ebcbbbdbeb13637d92cd845f70308af5e",
+            ),
+            (
+                "TREZOR",
+                "cdd74cbef2372344879b8a0aa8799435ff55bf5bde335638cb7a8d09fd0f9759",
+                "027440c6c46ec617a202f44bc886a249b10f98a8ff5d8a0aa56a350ab930a0ec79",
+            ),
+        ];
+        for (passphrase, seed_hex, public_key_hex) in cases {
+            let key = private_key_from_mnemonic(mnemonic, passphrase).unwrap();
+            assert_eq!(*key.expose_secret_hex(), seed_hex);
+            let public_key = key.public_key(Network::MAINNET);
+            assert_eq!(hex::encode(public_key.as_bytes()), public_key_hex);
+        }
+
+        // Unknown word and bad checksum
+        for bad in [
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon deso",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon",
+        ] {
+            assert!(matches!(
+                private_key_from_mnemonic(bad, ""),
+                Err(errors::DesoError::MnemonicError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_private_key_is_redacted() {
+        let seed_hex = "0101010101010101010101010101010101010101010101010101010101010101";
+        let key = PrivateKey::from_hex(seed_hex).unwrap();
+        assert_eq!(*key.expose_secret_hex(), seed_hex);
+        assert_eq!(
+            hex::encode(key.public_key(Network::MAINNET).as_bytes()),
+            KEY_HEX
+        );
+        for shown in [format!("{:?}", key), key.to_string()] {
+            assert_eq!(shown, "PrivateKey(<redacted>)");
+        }
+
+        for bad in ["not hex", "00", &"00".repeat(32)] {
+            assert!(matches!(
+                PrivateKey::from_hex(bad),
+                Err(errors::DesoError::PrivateKeyError(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "serialize-secrets")]
+    #[test]
+    fn test_private_key_serializes_with_feature() {
+        let seed_hex = "0101010101010101010101010101010101010101010101010101010101010101";
+        let key = PrivateKey::from_hex(seed_hex).unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, format!("\"{}\"", seed_hex));
+        let parsed: PrivateKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(*parsed.expose_secret_hex(), seed_hex);
+    }
+
+    #[test]
+    fn test_check_network() {
+        let key = PublicKey::from_bytes(&hex::decode(KEY_HEX).unwrap(), Network::TESTNET).unwrap();
+        assert!(key.check_network(Network::TESTNET).is_ok());
+        assert!(matches!(
+            key.check_network(Network::MAINNET),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+    }
+}
@@ -0,0 +1,526 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use crypto::scrypt::{scrypt, ScryptParams};
+use secp256k1::rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::errors;
+use crate::key_lib::{PrivateKey, PublicKey};
+use crate::{DesoAccount, Node};
+
+const KEYSTORE_VERSION: u32 = 1;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+/// Most memory a keystore may ask scrypt for, 1 GiB
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+/// Most scrypt passes a keystore may ask for
+const MAX_SCRYPT_P: u32 = 16;
+
+/// Scrypt cost parameters used to turn the keystore password into its
+/// encryption key. The defaults (N = 2^15, r = 8, p = 1) take a fraction of a
+/// second in release builds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeystoreKdf {
+    /// Log2 of the scrypt cost N
+    pub log_n: u8,
+    /// Scrypt block size
+    pub r: u32,
+    /// Scrypt parallelization
+    pub p: u32,
+}
+
+impl Default for KeystoreKdf {
+    fn default() -> Self {
+        KeystoreKdf {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+impl KeystoreKdf {
+    fn check(&self) -> Result<(), errors::DesoError> {
+        if self.log_n == 0 || self.log_n > 24 {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "Scrypt log_n {} is outside 1 to 24",
+                self.log_n
+            )));
+        }
+        if self.r == 0 || self.p == 0 || (self.log_n as u64) >= self.r as u64 * 16 {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "Invalid scrypt parameters r = {}, p = {}",
+                self.r, self.p
+            )));
+        }
+        // A keystore file read from disk must not be able to exhaust memory or time
+        let memory = (128 * self.r as u64) << self.log_n;
+        if memory > MAX_SCRYPT_MEMORY {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "Scrypt would use {} bytes of memory, more than {}",
+                memory, MAX_SCRYPT_MEMORY
+            )));
+        }
+        if self.p > MAX_SCRYPT_P {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "Scrypt p {} is more than {}",
+                self.p, MAX_SCRYPT_P
+            )));
+        }
+        Ok(())
+    }
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Zeroizing<[u8; 32]> {
+        let params = ScryptParams::new(self.log_n, self.r, self.p);
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt(password.as_bytes(), salt, &params, &mut *key);
+        key
+    }
+}
+
+/// The keystore file as written to disk. Only the accounts are encrypted.
+#[derive(Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    kdf: KeystoreKdf,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An account as held inside the encrypted part of the keystore
+#[derive(Serialize, Deserialize)]
+struct StoredAccount {
+    public_key: PublicKey,
+    seed_hex_key: String,
+    derived_public_key: Option<PublicKey>,
+    node: Node,
+}
+
+impl Drop for StoredAccount {
+    fn drop(&mut self) {
+        self.seed_hex_key.zeroize();
+    }
+}
+
+struct KeystoreEntry {
+    public_key: PublicKey,
+    seed_hex_key: PrivateKey,
+    derived_public_key: Option<PublicKey>,
+    node: Node,
+}
+
+/// Named Deso accounts kept in a password encrypted file.
+///
+/// The password is stretched with scrypt and the accounts are sealed with
+/// ChaCha20-Poly1305, so seeds are never written to disk in the clear. Every
+/// change is written straight back to the file, with a fresh nonce.
+pub struct Keystore {
+    path: PathBuf,
+    kdf: KeystoreKdf,
+    salt: [u8; SALT_LEN],
+    key: Zeroizing<[u8; 32]>,
+    accounts: BTreeMap<String, KeystoreEntry>,
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystore")
+            .field("path", &self.path)
+            .field("accounts", &self.list())
+            .finish()
+    }
+}
+
+fn keystore_error<E: fmt::Display>(context: &str, e: E) -> errors::DesoError {
+    errors::DesoError::KeystoreError(format!("{}: {}", context, e))
+}
+
+fn decode_hex_field(field: &str, value: &str, len: usize) -> Result<Vec<u8>, errors::DesoError> {
+    let bytes = hex::decode(value).map_err(|e| keystore_error(field, e))?;
+    if bytes.len() != len {
+        return Err(errors::DesoError::KeystoreError(format!(
+            "{} should be {} bytes, got {}",
+            field,
+            len,
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+impl Keystore {
+    /// Creates a new, empty keystore at `path` with the default scrypt cost.
+    /// Errors if the file already exists.
+    pub fn create(path: impl AsRef<Path>, password: &str) -> Result<Self, errors::DesoError> {
+        Keystore::create_with_kdf(path, password, KeystoreKdf::default())
+    }
+
+    /// Creates a new, empty keystore at `path`, deriving its key with `kdf`.
+    /// Errors if the file already exists.
+    pub fn create_with_kdf(
+        path: impl AsRef<Path>,
+        password: &str,
+        kdf: KeystoreKdf,
+    ) -> Result<Self, errors::DesoError> {
+        kdf.check()?;
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        secp256k1::rand::thread_rng().fill_bytes(&mut salt);
+        let keystore = Keystore {
+            path,
+            kdf,
+            salt,
+            key: kdf.derive_key(password, &salt),
+            accounts: BTreeMap::new(),
+        };
+        keystore.write()?;
+        Ok(keystore)
+    }
+
+    /// Opens the keystore at `path`. Errors if the password is wrong or the
+    /// file was tampered with.
+    pub fn open(path: impl AsRef<Path>, password: &str) -> Result<Self, errors::DesoError> {
+        let path = path.as_ref().to_path_buf();
+        let text = fs::read_to_string(&path)
+            .map_err(|e| keystore_error(&format!("Reading {}", path.display()), e))?;
+        let file: KeystoreFile =
+            serde_json::from_str(&text).map_err(|e| keystore_error("Parsing keystore", e))?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(errors::DesoError::KeystoreError(format!(
+                "Unsupported keystore version {}",
+                file.version
+            )));
+        }
+        file.kdf.check()?;
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&decode_hex_field("salt", &file.salt, SALT_LEN)?);
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&decode_hex_field("nonce", &file.nonce, NONCE_LEN)?);
+        let ciphertext =
+            hex::decode(&file.ciphertext).map_err(|e| keystore_error("ciphertext", e))?;
+
+        let key = file.kdf.derive_key(password, &salt);
+        let cipher = ChaCha20Poly1305::new(&Key::from(*key));
+        let plaintext = match cipher.decrypt(&Nonce::from(nonce), ciphertext.as_slice()) {
+            Ok(p) => Zeroizing::new(p),
+            Err(_) => {
+                return Err(errors::DesoError::KeystoreError(String::from(
+                    "Wrong password or corrupted keystore",
+                )))
+            }
+        };
+        let stored: BTreeMap<String, StoredAccount> = serde_json::from_slice(&plaintext)
+            .map_err(|e| keystore_error("Parsing accounts", e))?;
+        let mut accounts = BTreeMap::new();
+        for (name, account) in stored {
+            let entry = KeystoreEntry {
+                public_key: account.public_key.clone(),
+                seed_hex_key: PrivateKey::from_hex(&account.seed_hex_key)?,
+                derived_public_key: account.derived_public_key.clone(),
+                node: account.node.clone(),
+            };
+            accounts.insert(name, entry);
+        }
+        Ok(Keystore {
+            path,
+            kdf: file.kdf,
+            salt,
+            key,
+            accounts,
+        })
+    }
+
+    /// The file the keystore is kept in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The names of the stored accounts, in order
+    pub fn list(&self) -> Vec<&str> {
+        self.accounts.keys().map(String::as_str).collect()
+    }
+
+    /// Whether an account is stored under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.accounts.contains_key(name)
+    }
+
+    /// The account stored under `name`
+    pub fn load(&self, name: &str) -> Result<DesoAccount, errors::DesoError> {
+        match self.accounts.get(name) {
+            Some(entry) => Ok(DesoAccount {
+                public_key: entry.public_key.clone(),
+                seed_hex_key: entry.seed_hex_key.clone(),
+                derived_public_key: entry.derived_public_key.clone(),
+                node: entry.node.clone(),
+            }),
+            None => Err(errors::DesoError::KeystoreError(format!(
+                "No account named {}",
+                name
+            ))),
+        }
+    }
+
+    /// Stores `account` under `name`, replacing any account already there,
+    /// and writes the keystore
+    pub fn save(&mut self, name: &str, account: &DesoAccount) -> Result<(), errors::DesoError> {
+        if name.is_empty() {
+            return Err(errors::DesoError::KeystoreError(String::from(
+                "Account name is empty",
+            )));
+        }
+        let entry = KeystoreEntry {
+            public_key: account.public_key.clone(),
+            seed_hex_key: account.seed_hex_key.clone(),
+            derived_public_key: account.derived_public_key.clone(),
+            node: account.node.clone(),
+        };
+        let previous = self.accounts.insert(name.to_string(), entry);
+        if let Err(e) = self.write() {
+            // Keep memory in line with what is on disk
+            match previous {
+                Some(previous) => self.accounts.insert(name.to_string(), previous),
+                None => self.accounts.remove(name),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Removes the account stored under `name` and writes the keystore.
+    /// Returns whether there was one.
+    pub fn remove(&mut self, name: &str) -> Result<bool, errors::DesoError> {
+        let previous = match self.accounts.remove(name) {
+            Some(p) => p,
+            None => return Ok(false),
+        };
+        if let Err(e) = self.write() {
+            self.accounts.insert(name.to_string(), previous);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Encrypts the accounts and replaces the file with them
+    fn write(&self) -> Result<(), errors::DesoError> {
+        let stored: BTreeMap<&str, StoredAccount> = self
+            .accounts
+            .iter()
+            .map(|(name, entry)| {
+                let account = StoredAccount {
+                    public_key: entry.public_key.clone(),
+                    seed_hex_key: entry.seed_hex_key.expose_secret_hex().to_string(),
+                    derived_public_key: entry.derived_public_key.clone(),
+                    node: entry.node.clone(),
+                };
+                (name.as_str(), account)
+            })
+            .collect();
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(&stored).map_err(|e| keystore_error("Encoding accounts", e))?,
+        );
+
+        let mut nonce = [0u8; NONCE_LEN];
+        secp256k1::rand::thread_rng().fill_bytes(&mut nonce);
+        let cipher = ChaCha20Poly1305::new(&Key::from(*self.key));
+        let ciphertext = cipher
+            .encrypt(&Nonce::from(nonce), plaintext.as_slice())
+            .map_err(|e| keystore_error("Encrypting accounts", e))?;
+        let file = KeystoreFile {
+            version: KEYSTORE_VERSION,
+            kdf: self.kdf,
+            salt: hex::encode(self.salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        let text = serde_json::to_string_pretty(&file)
+            .map_err(|e| keystore_error("Encoding keystore", e))?;
+
+        // Write next to the keystore and rename over it, so a crash never
+        // leaves a half written file
+        let mut tmp_name = self.path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_name);
+        write_private_file(&tmp_path, text.as_bytes())
+            .map_err(|e| keystore_error(&format!("Writing {}", tmp_path.display()), e))?;
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| keystore_error(&format!("Writing {}", self.path.display()), e))
+    }
+}
+
+/// Writes a file only the current user can read
+fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DesoAccountBuilder, Network};
+
+    const SEED_HEX: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    // Cheap enough for debug test builds
+    const TEST_KDF: KeystoreKdf = KeystoreKdf {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "deso-keystore-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let path = temp_path("round-trip");
+        let mut keystore = Keystore::create_with_kdf(&path, "hunter2", TEST_KDF).unwrap();
+        assert!(keystore.list().is_empty());
+
+        let owner = DesoAccountBuilder::new()
+            .seed_hex_key(String::from(SEED_HEX))
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        let derived = PrivateKey::generate();
+        let bot = DesoAccountBuilder::new()
+            .public_key(owner.public_key().clone())
+            .private_key(derived.clone())
+            .derived_public_key(derived.public_key(Network::TESTNET))
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        keystore.save("owner", &owner).unwrap();
+        keystore.save("bot", &bot).unwrap();
+        assert_eq!(keystore.list(), vec!["bot", "owner"]);
+
+        // Nothing secret is written in the clear
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(!text.contains(SEED_HEX));
+        assert!(!text.contains(&*derived.expose_secret_hex()));
+        assert!(!text.contains(&owner.public_key().to_string()));
+
+        let reopened = Keystore::open(&path, "hunter2").unwrap();
+        assert_eq!(reopened.list(), vec!["bot", "owner"]);
+        let loaded = reopened.load("owner").unwrap();
+        assert_eq!(loaded.public_key(), owner.public_key());
+        assert_eq!(*loaded.seed_hex_key.expose_secret_hex(), SEED_HEX);
+        assert_eq!(loaded.node(), &Node::TEST);
+        let loaded = reopened.load("bot").unwrap();
+        assert_eq!(loaded.public_key(), owner.public_key());
+        assert_eq!(loaded.derived_public_key(), bot.derived_public_key());
+        assert_eq!(
+            *loaded.seed_hex_key.expose_secret_hex(),
+            *derived.expose_secret_hex()
+        );
+        assert!(matches!(
+            reopened.load("missing"),
+            Err(errors::DesoError::KeystoreError(_))
+        ));
+
+        let mut keystore = reopened;
+        assert!(keystore.remove("bot").unwrap());
+        assert!(!keystore.remove("bot").unwrap());
+        assert_eq!(
+            Keystore::open(&path, "hunter2").unwrap().list(),
+            vec!["owner"]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_keystore_rejects_bad_password_and_tampering() {
+        let path = temp_path("tamper");
+        let mut keystore = Keystore::create_with_kdf(&path, "hunter2", TEST_KDF).unwrap();
+        let owner = DesoAccountBuilder::new()
+            .seed_hex_key(String::from(SEED_HEX))
+            .build()
+            .unwrap();
+        keystore.save("owner", &owner).unwrap();
+
+        assert!(matches!(
+            Keystore::open(&path, "hunter3"),
+            Err(errors::DesoError::KeystoreError(_))
+        ));
+        assert!(matches!(
+            Keystore::create_with_kdf(&path, "hunter2", TEST_KDF),
+            Err(errors::DesoError::KeystoreError(_))
+        ));
+
+        let mut file: KeystoreFile =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut ciphertext = hex::decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        file.ciphertext = hex::encode(ciphertext);
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        assert!(matches!(
+            Keystore::open(&path, "hunter2"),
+            Err(errors::DesoError::KeystoreError(_))
+        ));
+        fs::remove_file(&path).unwrap();
+
+        // An oversized header is refused before any key is derived
+        for kdf in [
+            KeystoreKdf {
+                log_n: 20,
+                r: 1024,
+                p: 1,
+            },
+            KeystoreKdf {
+                p: 1_000_000,
+                ..TEST_KDF
+            },
+        ] {
+            let path = temp_path("oversized");
+            Keystore::create_with_kdf(&path, "hunter2", TEST_KDF).unwrap();
+            let mut file: KeystoreFile =
+                serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            file.kdf = kdf;
+            fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+            assert!(matches!(
+                Keystore::open(&path, "hunter2"),
+                Err(errors::DesoError::KeystoreError(_))
+            ));
+            fs::remove_file(&path).unwrap();
+        }
+
+        let bad_kdf = KeystoreKdf {
+            log_n: 0,
+            ..TEST_KDF
+        };
+        assert!(matches!(
+            Keystore::create_with_kdf(temp_path("bad-kdf"), "hunter2", bad_kdf),
+            Err(errors::DesoError::KeystoreError(_))
+        ));
+    }
+}
@@ -1,13 +1,60 @@
+mod client_lib;
 mod crypto_lib;
+mod derived_key_lib;
 mod errors;
+mod jwt_lib;
+mod key_lib;
+mod keystore_lib;
 mod post_lib;
-pub use post_lib::SubmitPostDataBuilder;
-use reqwest;
+mod signer_lib;
+mod submit_lib;
+mod txn_lib;
+pub use client_lib::{DesoClient, DesoClientBuilder, TxnVerifier, DEFAULT_FEE_RATE};
+pub use derived_key_lib::{
+    generate_derived_key, AccessGroupLimitKey, AccessGroupMemberLimitKey,
+    AccessGroupMemberOperation, AccessGroupOperation, AssociationClass, AssociationLimitKey,
+    AssociationOperation, CreatorCoinLimitKey, CreatorCoinLimitOperation, DAOCoinLimitKey,
+    DAOCoinLimitOperation, DerivedKeyEntry, DerivedKeyPair, NFTLimitKey, NFTLimitOperation,
+    RemainingSpendingLimit, TransactionSpendingLimit, TransactionSpendingLimitBuilder,
+    MAX_ACCESS_GROUP_KEY_NAME_LEN,
+};
+pub use errors::DesoError;
+pub use key_lib::{PrivateKey, PublicKey};
+pub use keystore_lib::{Keystore, KeystoreKdf};
+pub use post_lib::{
+    PostEntryReaderState, PostEntryResponse, SinglePostOptions, SubmitPostDataBuilder,
+};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
-use serde_json;
+pub use signer_lib::Signer;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+pub use submit_lib::{Sleeper, SubmissionOutcome, SubmitPolicy, SubmitPolicyBuilder, TokioSleeper};
+pub use txn_lib::{
+    verify_signed_transaction, AuthorizeDerivedKeyMetadata, DesoInput, DesoOutput, LikeMetadata,
+    MsgDeSoTxn, PostExpectations, SubmitPostMetadata, TxnExpectations, TxnMeta, TxnNonce,
+    DERIVED_PUBLIC_KEY, HASH_LEN, PUBLIC_KEY_LEN, REPOSTED_POST_HASH,
+};
+pub use txn_lib::{
+    TXN_TYPE_ACCEPT_NFT_BID, TXN_TYPE_ACCEPT_NFT_TRANSFER, TXN_TYPE_ACCESS_GROUP,
+    TXN_TYPE_ACCESS_GROUP_MEMBERS, TXN_TYPE_ATOMIC_TXNS_WRAPPER, TXN_TYPE_AUTHORIZE_DERIVED_KEY,
+    TXN_TYPE_BASIC_TRANSFER, TXN_TYPE_BITCOIN_EXCHANGE, TXN_TYPE_BLOCK_REWARD, TXN_TYPE_BURN_NFT,
+    TXN_TYPE_COIN_LOCKUP, TXN_TYPE_COIN_LOCKUP_TRANSFER, TXN_TYPE_COIN_UNLOCK, TXN_TYPE_CREATE_NFT,
+    TXN_TYPE_CREATE_POST_ASSOCIATION, TXN_TYPE_CREATE_USER_ASSOCIATION, TXN_TYPE_CREATOR_COIN,
+    TXN_TYPE_CREATOR_COIN_TRANSFER, TXN_TYPE_DAO_COIN, TXN_TYPE_DAO_COIN_LIMIT_ORDER,
+    TXN_TYPE_DAO_COIN_TRANSFER, TXN_TYPE_DELETE_POST_ASSOCIATION, TXN_TYPE_DELETE_USER_ASSOCIATION,
+    TXN_TYPE_FOLLOW, TXN_TYPE_LIKE, TXN_TYPE_MESSAGING_GROUP, TXN_TYPE_NEW_MESSAGE,
+    TXN_TYPE_NFT_BID, TXN_TYPE_NFT_TRANSFER, TXN_TYPE_PRIVATE_MESSAGE,
+    TXN_TYPE_REGISTER_AS_VALIDATOR, TXN_TYPE_STAKE, TXN_TYPE_SUBMIT_POST, TXN_TYPE_SWAP_IDENTITY,
+    TXN_TYPE_UNJAIL_VALIDATOR, TXN_TYPE_UNLOCK_STAKE, TXN_TYPE_UNREGISTER_AS_VALIDATOR,
+    TXN_TYPE_UNSTAKE, TXN_TYPE_UPDATE_BITCOIN_USD_EXCHANGE_RATE,
+    TXN_TYPE_UPDATE_COIN_LOCKUP_PARAMS, TXN_TYPE_UPDATE_GLOBAL_PARAMS, TXN_TYPE_UPDATE_NFT,
+    TXN_TYPE_UPDATE_PROFILE,
+};
+use zeroize::Zeroize;
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 struct TransactionFee {
     #[serde(rename = "PublicKeyBase58Check")]
@@ -41,37 +88,187 @@ struct SignatureIndex {
     signature_index: u32,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+enum TxnStatus {
+    InMempool,
+    Committed,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct GetTransactionRequest {
+    #[serde(rename = "TxnHashHex")]
+    txn_hash_hex: String,
+    #[serde(rename = "TxnStatus")]
+    txn_status: TxnStatus,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct GetTransaction {
     #[serde(rename = "TxnFound")]
     txn_found: bool,
 }
 
-/// Determines whether to target the Main node or Test node
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug)]
+struct TransactionInfoRequest {
+    #[serde(rename = "TransactionIDBase58Check")]
+    transaction_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct TransactionInfo {
+    #[serde(rename = "Transactions", default)]
+    transactions: Vec<TransactionInfoEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct TransactionInfoEntry {
+    #[serde(rename = "BlockHashHex")]
+    block_hash_hex: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BlockRequest {
+    #[serde(rename = "HashHex")]
+    hash_hex: String,
+    #[serde(rename = "FullBlock")]
+    full_block: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Block {
+    #[serde(rename = "Header")]
+    header: BlockHeader,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BlockHeader {
+    #[serde(rename = "Height")]
+    height: u64,
+}
+
+/// The Deso network a node belongs to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+    MAINNET,
+    TESTNET,
+}
+
+/// Connection details for a self-hosted, regtest or mock Deso node. Built
+/// with `NodeConfig::new`, which checks the url, including when deserialized.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "NodeConfigFields")]
+pub struct NodeConfig {
+    base_url: String,
+    network: Network,
+    api_prefix: Option<String>,
+}
+
+/// The fields of a serialized `NodeConfig`, checked through `NodeConfig::new`
+#[derive(Deserialize)]
+struct NodeConfigFields {
+    base_url: String,
+    network: Network,
+    api_prefix: Option<String>,
+}
+
+impl TryFrom<NodeConfigFields> for NodeConfig {
+    type Error = errors::DesoError;
+
+    fn try_from(fields: NodeConfigFields) -> Result<Self, Self::Error> {
+        let config = NodeConfig::new(&fields.base_url, fields.network)?;
+        Ok(match fields.api_prefix {
+            Some(prefix) => config.api_prefix(&prefix),
+            None => config,
+        })
+    }
+}
+
+impl NodeConfig {
+    /// Creates a node config, checking that `base_url` is a valid http(s) url
+    pub fn new(base_url: &str, network: Network) -> Result<Self, errors::DesoError> {
+        let url = match reqwest::Url::parse(base_url) {
+            Ok(u) => u,
+            Err(e) => return Err(errors::DesoError::NodeConfigError(e.to_string())),
+        };
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(errors::DesoError::NodeConfigError(format!(
+                "Unsupported scheme {}",
+                url.scheme()
+            )));
+        }
+        Ok(NodeConfig {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            network,
+            api_prefix: None,
+        })
+    }
+    /// An optional path prefix placed in front of every api path, e.g. `deso`
+    pub fn api_prefix(mut self, api_prefix: &str) -> Self {
+        let prefix = api_prefix.trim_matches('/');
+        self.api_prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_string())
+        };
+        self
+    }
+    /// The base url of the node, e.g. `http://localhost:17001`
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    /// The network the node is running on
+    pub fn network(&self) -> Network {
+        self.network
+    }
+    /// The path prefix set with `api_prefix`, if any
+    pub fn prefix(&self) -> Option<&str> {
+        self.api_prefix.as_deref()
+    }
+}
+
+/// Determines whether to target the Main node, Test node or a custom node
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Node {
     MAIN,
-    TEST
+    TEST,
+    CUSTOM(NodeConfig),
 }
 
 impl Node {
-    fn get_endpoint(self, api: &str) -> String {
+    fn get_endpoint(&self, api: &str) -> String {
         match self {
             Node::MAIN => format!("https://node.deso.org/{}", api),
-            Node::TEST => format!("https://test.deso.org/{}", api)
+            Node::TEST => format!("https://test.deso.org/{}", api),
+            Node::CUSTOM(config) => match &config.api_prefix {
+                Some(prefix) => format!("{}/{}/{}", config.base_url, prefix, api),
+                None => format!("{}/{}", config.base_url, api),
+            },
+        }
+    }
+
+    /// The network this node is running on
+    pub fn network(&self) -> Network {
+        match self {
+            Node::MAIN => Network::MAINNET,
+            Node::TEST => Network::TESTNET,
+            Node::CUSTOM(config) => config.network,
         }
     }
 }
 
-/// A Deso account that will be used to do any transactions
-#[derive(Serialize, Deserialize, Debug)]
+/// A Deso account that will be used to do any transactions.
+///
+/// Debug output redacts the private key. The account can only be serialized
+/// with the `serialize-secrets` feature, as that writes out the private key.
+#[derive(Debug)]
+#[cfg_attr(feature = "serialize-secrets", derive(Serialize, Deserialize))]
 pub struct DesoAccount {
     /// The deso account public key
-    public_key: String,
+    public_key: PublicKey,
     /// Either the seed hex or derived private key (recommended)
-    seed_hex_key: String,
+    seed_hex_key: PrivateKey,
     /// The derived public key (needed if using a derived private key)
-    derived_public_key: Option<String>,
+    derived_public_key: Option<PublicKey>,
     /// The Node you are targeting (Main is default)
     node: Node,
 }
@@ -79,8 +276,17 @@ pub struct DesoAccount {
 pub struct DesoAccountBuilder {
     pub public_key: Option<String>,
     pub seed_hex_key: Option<String>,
+    pub private_key: Option<PrivateKey>,
     pub derived_public_key: Option<String>,
     pub node: Option<Node>,
+    pub mnemonic: Option<String>,
+    pub passphrase: Option<String>,
+}
+
+impl Default for DesoAccountBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DesoAccountBuilder {
@@ -88,23 +294,34 @@ impl DesoAccountBuilder {
         DesoAccountBuilder {
             public_key: None,
             seed_hex_key: None,
+            private_key: None,
             derived_public_key: None,
-            node: Some(Node::MAIN)
+            node: Some(Node::MAIN),
+            mnemonic: None,
+            passphrase: None,
         }
     }
-    /// The deso account public key
-    pub fn public_key(mut self, public_key: String) -> Self {
-        self.public_key = Some(public_key);
+    /// The deso account public key, in Base58Check. Derived from the seed hex
+    /// when not set, unless the account signs with a derived key
+    pub fn public_key(mut self, public_key: impl Into<String>) -> Self {
+        self.public_key = Some(public_key.into());
         self
     }
     /// Either the seed hex or derived private key (recommended)
     pub fn seed_hex_key(mut self, seed_hex_key: String) -> Self {
         self.seed_hex_key = Some(seed_hex_key);
+        self.private_key = None;
         self
     }
-    /// The derived public key (needed if using a derived private key)
-    pub fn derived_public_key(mut self, derived_public_key: String) -> Self {
-        self.derived_public_key = Some(derived_public_key);
+    /// Either the seed hex or derived private key, already parsed
+    pub fn private_key(mut self, private_key: PrivateKey) -> Self {
+        self.private_key = Some(private_key);
+        self.seed_hex_key = None;
+        self
+    }
+    /// The derived public key (needed if using a derived private key), in Base58Check
+    pub fn derived_public_key(mut self, derived_public_key: impl Into<String>) -> Self {
+        self.derived_public_key = Some(derived_public_key.into());
         self
     }
     /// The Node you are targeting
@@ -112,28 +329,109 @@ impl DesoAccountBuilder {
         self.node = Some(node);
         self
     }
-    /// Builds the DesoAccount
-    pub fn build(self) -> Result<DesoAccount, errors::DesoError> {
-        if self.public_key.is_none() {
+    /// A BIP39 mnemonic to derive the owner seed hex from, in place of `seed_hex_key`
+    pub fn mnemonic(mut self, mnemonic: impl Into<String>) -> Self {
+        self.mnemonic = Some(mnemonic.into());
+        self
+    }
+    /// The optional BIP39 passphrase used with the mnemonic
+    pub fn passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+    /// Builds the DesoAccount, checking the keys are valid for the node's network
+    pub fn build(mut self) -> Result<DesoAccount, errors::DesoError> {
+        if let Some(mut mnemonic) = self.mnemonic.take() {
+            if self.seed_hex_key.is_some() || self.private_key.is_some() {
+                return Err(errors::DesoError::MnemonicError(String::from(
+                    "Give either a mnemonic or a seed hex, not both",
+                )));
+            }
+            let mut passphrase = self.passphrase.take().unwrap_or_default();
+            let private_key = key_lib::private_key_from_mnemonic(&mnemonic, &passphrase);
+            mnemonic.zeroize();
+            passphrase.zeroize();
+            self.private_key = Some(private_key?);
+        }
+        if let Some(mut seed_hex_key) = self.seed_hex_key.take() {
+            let private_key = PrivateKey::from_hex(&seed_hex_key);
+            seed_hex_key.zeroize();
+            self.private_key = Some(private_key?);
+        }
+        if self.public_key.is_none() && self.derived_public_key.is_some() {
+            // A derived private key can't tell us who its owner is
             return Err(errors::DesoError::DesoAccountError(String::from(
                 "Public Key",
             )));
         }
-        if self.seed_hex_key.is_none() {
-            return Err(errors::DesoError::DesoAccountError(String::from(
-                "Seed Hex or Derived Private Key",
-            )));
+        let seed_hex_key = match self.private_key {
+            Some(key) => key,
+            None => {
+                return Err(errors::DesoError::DesoAccountError(String::from(
+                    "Seed Hex or Derived Private Key",
+                )))
+            }
+        };
+        let node = self.node.unwrap();
+        let derived_public_key = match self.derived_public_key {
+            Some(key) => {
+                let key = PublicKey::from_base58_check(&key)?;
+                key.check_network(node.network())?;
+                Some(key)
+            }
+            None => None,
+        };
+        let public_key = match self.public_key {
+            Some(key) => {
+                let key = PublicKey::from_base58_check(&key)?;
+                key.check_network(node.network())?;
+                key
+            }
+            None => seed_hex_key.public_key(node.network()),
+        };
+        // Without a derived key the seed is the owner's, so it has to match the public key
+        if derived_public_key.is_none() {
+            let owner = seed_hex_key.public_key(node.network());
+            if owner != public_key {
+                return Err(errors::DesoError::KeyMismatch(format!(
+                    "Seed Hex is not for {}, it belongs to {}",
+                    public_key, owner
+                )));
+            }
         }
         Ok(DesoAccount {
-            public_key: self.public_key.unwrap(),
-            seed_hex_key: self.seed_hex_key.unwrap(),
-            derived_public_key: self.derived_public_key,
-            node: self.node.unwrap()
+            public_key,
+            seed_hex_key,
+            derived_public_key,
+            node,
         })
     }
 }
 
+impl DesoAccount {
+    /// The deso account public key
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+    /// The derived public key, if the account signs with a derived key
+    pub fn derived_public_key(&self) -> Option<&PublicKey> {
+        self.derived_public_key.as_ref()
+    }
+    /// The Node the account targets
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+    /// An ES256 JWT for the node endpoints that need one, valid for `expiry`.
+    ///
+    /// Signed with the account's key; accounts signing with a derived key
+    /// carry it in the `derivedPublicKeyBase58Check` claim.
+    pub fn jwt(&self, expiry: Duration) -> Result<String, errors::DesoError> {
+        jwt_lib::sign_jwt(&self.seed_hex_key, self.derived_public_key.as_ref(), expiry)
+    }
+}
+
 #[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms)]
 #[allow(dead_code)]
 enum TransactionType {
     POST,
@@ -147,58 +445,133 @@ enum TransactionType {
     AUTHORIZE,
     UPDATE,
     ASSOCIATION,
+    LIKE,
+}
+
+impl TransactionType {
+    /// The wire txn type the node should build for this kind of transaction,
+    /// where the SDK knows it
+    fn txn_type_code(&self) -> Option<u64> {
+        match self {
+            TransactionType::POST => Some(txn_lib::TXN_TYPE_SUBMIT_POST),
+            TransactionType::AUTHORIZE => Some(txn_lib::TXN_TYPE_AUTHORIZE_DERIVED_KEY),
+            TransactionType::LIKE => Some(txn_lib::TXN_TYPE_LIKE),
+            _ => None,
+        }
+    }
 }
 
 const DEBUG: bool = false;
 
+/// Creates a new post or comment signed by `publisher_account`.
 pub async fn create_post(
     publisher_account: &DesoAccount,
     post_data: &post_lib::SubmitPostData,
 ) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
-    let client = reqwest::Client::new();
-    let post_uri = publisher_account.node.get_endpoint("api/v0/submit-post");
-
-    let post_transaction_response = submit_and_sign(
-        &publisher_account.node,
-        &post_uri,
-        &client,
-        &post_data,
-        1,
-        TransactionType::POST,
-        publisher_account.seed_hex_key.clone(),
-        publisher_account.derived_public_key.clone(),
-    )
-    .await?;
-    let transaction_json: post_lib::SubmittedTransaction =
-        match serde_json::from_str(&post_transaction_response.to_string()) {
-            Ok(j) => j,
-            Err(e) => {
-                return Err(errors::DesoError::JsonError(
-                    String::from("NEW POST ERROR"),
-                    e.to_string(),
-                ))
-            }
-        };
-    let _post_hash_hex = transaction_json.post_entry_response.post_hash_hex.clone();
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client.create_post_as(publisher_account, post_data).await
+}
+
+/// Reposts the post with hash `post_hash_hex`.
+pub async fn repost(
+    publisher_account: &DesoAccount,
+    post_hash_hex: &str,
+) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client.repost_as(publisher_account, post_hash_hex).await
+}
+
+/// Quotes the post with hash `post_hash_hex` with `body`.
+pub async fn quote_repost(
+    publisher_account: &DesoAccount,
+    post_hash_hex: &str,
+    body: String,
+) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client
+        .quote_repost_as(publisher_account, post_hash_hex, body)
+        .await
+}
+
+/// Likes the post with hash `post_hash_hex`, or takes the like back if
+/// `unlike` is set.
+pub async fn like_post(
+    account: &DesoAccount,
+    post_hash_hex: &str,
+    unlike: bool,
+) -> Result<SubmissionOutcome, errors::DesoError> {
+    let client = DesoClient::shared(account.node.clone());
+    client.like_post_as(account, post_hash_hex, unlike).await
+}
+
+/// Edits the post named by `post_data.post_hash_hex_to_modify`, signed by
+/// `publisher_account`. Only the original poster can edit a post.
+pub async fn edit_post(
+    publisher_account: &DesoAccount,
+    post_data: &post_lib::SubmitPostData,
+) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client.edit_post_as(publisher_account, post_data).await
+}
 
-    return Ok(transaction_json);
+/// Hides the post with hash `post_hash_hex`, the closest Deso has to
+/// deleting it. Only the original poster can hide a post.
+pub async fn hide_post(
+    publisher_account: &DesoAccount,
+    post_hash_hex: &str,
+) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client.hide_post_as(publisher_account, post_hash_hex).await
+}
+
+/// Shows a hidden post with hash `post_hash_hex` again.
+pub async fn unhide_post(
+    publisher_account: &DesoAccount,
+    post_hash_hex: &str,
+) -> Result<post_lib::SubmittedTransaction, errors::DesoError> {
+    let client = DesoClient::shared(publisher_account.node.clone());
+    client
+        .unhide_post_as(publisher_account, post_hash_hex)
+        .await
+}
+
+/// Authorizes `derived_public_key` to sign for `owner` until `expiration_block`,
+/// within `spending_limit`.
+pub async fn authorize_derived_key(
+    owner: &DesoAccount,
+    derived_public_key: &PublicKey,
+    expiration_block: u64,
+    spending_limit: &TransactionSpendingLimit,
+) -> Result<SubmissionOutcome, errors::DesoError> {
+    let client = DesoClient::shared(owner.node.clone());
+    client
+        .authorize_derived_key(owner, derived_public_key, expiration_block, spending_limit)
+        .await
+}
+
+/// Revokes `derived_public_key`, so it can no longer sign for `owner`.
+pub async fn revoke_derived_key(
+    owner: &DesoAccount,
+    derived_public_key: &PublicKey,
+) -> Result<SubmissionOutcome, errors::DesoError> {
+    let client = DesoClient::shared(owner.node.clone());
+    client.revoke_derived_key(owner, derived_public_key).await
 }
 
 async fn get_signature_index(
-    node: &Node,
-    tx_hex: &String,
-    client: &reqwest::Client,
+    client: &DesoClient,
+    tx_hex: &str,
 ) -> Result<usize, errors::DesoError> {
-    let uri = node.get_endpoint("api/v0/signature-index");
+    let uri = client.node.get_endpoint("api/v0/signature-index");
     let payload = TransactionHex {
-        transaction_hex: tx_hex.clone(),
+        transaction_hex: tx_hex.to_string(),
     };
-    let resp = match client.post(uri).json(&payload).send().await {
+    let resp = match client.http.post(uri).json(&payload).send().await {
         Ok(s) => s,
         Err(e) => {
             return Err(errors::DesoError::SigningError(format!(
                 "Problem getting index response: {}",
-                e.to_string()
+                e
             )));
         }
     };
@@ -214,7 +587,7 @@ async fn get_signature_index(
         Err(e) => {
             return Err(errors::DesoError::SigningError(format!(
                 "Problem parsing index response: {}",
-                e.to_string()
+                e
             )));
         }
     };
@@ -222,15 +595,13 @@ async fn get_signature_index(
 }
 
 async fn submit_and_sign<T: Serialize + ?Sized>(
-    node: &Node,
+    client: &DesoClient,
     uri: &str,
-    client: &reqwest::Client,
     json: &T,
-    retry: u8,
     tx_type: TransactionType,
-    signer_hex: String,
-    derived_public_key: Option<String>,
-) -> Result<String, errors::DesoError> {
+    signer: &dyn Signer,
+    mut expected: TxnExpectations,
+) -> Result<(String, SubmissionOutcome), errors::DesoError> {
     let transaction = match tx_type {
         TransactionType::MINT => "minting",
         TransactionType::TRANS => "transfer",
@@ -243,16 +614,17 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
         TransactionType::AUTHORIZE => "authorizing dervied key",
         TransactionType::UPDATE => "updating nft to be for sale",
         TransactionType::ASSOCIATION => "associating a new author",
+        TransactionType::LIKE => "liking",
     };
     if DEBUG {
         println!("Logging for: {} transaction.", transaction);
     }
-    let resp = match client.post(uri).json(&json).send().await {
+    let resp = match client.http.post(uri).json(&json).send().await {
         Ok(s) => s,
         Err(e) => {
             return Err(errors::DesoError::TransactionError(
                 String::from(transaction),
-                format!("Error on Post: {}", e.to_string()),
+                format!("Error on Post: {}", e),
             ));
         }
     };
@@ -268,7 +640,7 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
         Err(e) => {
             return Err(errors::DesoError::TransactionError(
                 String::from(transaction),
-                format!("Problem in Response: {}; {}", text, e.to_string()),
+                format!("Problem in Response: {}; {}", text, e),
             ))
         }
     };
@@ -276,9 +648,12 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
         println!("BEFORE TX: {}", json.transaction_hex);
     }
     let mut tx_hex = json;
+    let derived_public_key = signer.derived_public_key();
     if let Some(key) = derived_public_key {
-        println!("Derived Public Key: {}", key);
-        tx_hex = match append_data(node, &tx_hex, key.to_string(), client).await {
+        if DEBUG {
+            println!("Derived Public Key: {}", key);
+        }
+        tx_hex = match append_data(client, &tx_hex, key.to_base58_check()).await {
             Ok(t) => t,
             Err(e) => {
                 return Err(errors::DesoError::TransactionError(
@@ -292,27 +667,69 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
         println!("\nAfter appending data: {}", tx_hex.transaction_hex);
     }
 
-    // Get signature index
-    let signature_index = get_signature_index(node, &tx_hex.transaction_hex, client).await?;
+    // Check the node built what we asked for before signing it
+    if expected.txn_type.is_none() {
+        expected.txn_type = tx_type.txn_type_code();
+    }
+    if expected.max_fee_nanos.is_none() {
+        expected.max_fee_nanos = client.max_fee_nanos;
+    }
+    if let Some(key) = derived_public_key {
+        expected.derived_public_key = Some(key.as_bytes().to_vec());
+    }
+    let tx_bytes = match hex::decode(&tx_hex.transaction_hex) {
+        Ok(b) => b,
+        Err(e) => return Err(errors::DesoError::TxnDecodingError(e.to_string())),
+    };
+    let (decoded, local_signature_index) = MsgDeSoTxn::decode(&tx_bytes)?;
+    decoded.verify(&expected)?;
+    if let Some(verifier) = &client.txn_verifier {
+        verifier(&decoded)?;
+    }
+
+    // Get signature index, from the decoded transaction unless the client asks the node
+    let signature_index = if client.remote_signature_index {
+        get_signature_index(client, &tx_hex.transaction_hex).await?
+    } else {
+        local_signature_index
+    };
 
-    let signed_transaction = crypto_lib::sign(tx_hex.transaction_hex, signer_hex, signature_index)?;
+    crypto_lib::check_unsigned(&tx_bytes, signature_index)?;
+    let signature = signer.sign_transaction(&tx_bytes).await?;
+    // Signers may live outside the process, so check what came back
+    crypto_lib::verify_bytes(
+        &tx_bytes,
+        &signature,
+        signer.signing_public_key().as_bytes(),
+    )?;
+    let signed_transaction = crypto_lib::insert_signature(&tx_bytes, signature_index, &signature)?;
 
     if DEBUG {
         println!("\nAfter signing: {}", signed_transaction);
     }
+    submit_signed(client, signed_transaction, transaction).await
+}
+
+/// Submits an already signed transaction, retrying and then waiting for it
+/// according to the client's policy. Returns the node's raw response.
+async fn submit_signed(
+    client: &DesoClient,
+    signed_transaction: String,
+    transaction: &str,
+) -> Result<(String, SubmissionOutcome), errors::DesoError> {
     let json_transaction_hex: TransactionHex = TransactionHex {
         transaction_hex: signed_transaction,
     };
-    let mut i = 0;
+    let policy = &client.policy;
+    let mut attempt: u32 = 0;
     let mut txn_hash_hex: TransactionSubmittedHex = TransactionSubmittedHex {
         txn_hash_hex: String::from(""),
     };
 
     let mut response_message = String::from("success");
 
-    while i < retry {
-        i += 1;
-        match submit_transaction(node, &json_transaction_hex, client).await {
+    while attempt <= policy.max_retries as u32 {
+        match submit_transaction(client, &json_transaction_hex).await {
             Ok(s) => {
                 response_message = s.clone();
                 txn_hash_hex = match serde_json::from_str(&s) {
@@ -326,14 +743,16 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
                 };
                 break;
             }
-            Err(e) => {
-                std::thread::sleep(std::time::Duration::from_secs(1 << i));
-                println!("Error {}", e.to_string());
+            Err(_) => {
+                if attempt < policy.max_retries as u32 {
+                    client.sleeper.sleep(policy.backoff(attempt)).await;
+                }
             }
         }
+        attempt += 1;
     }
 
-    if txn_hash_hex.txn_hash_hex == String::from("") {
+    if txn_hash_hex.txn_hash_hex.is_empty() {
         return Err(errors::DesoError::TransactionError(
             String::from(transaction),
             String::from("Transaction Failed :/"),
@@ -344,63 +763,151 @@ async fn submit_and_sign<T: Serialize + ?Sized>(
 
     // Now we have submitted a transaction successfully, but let's wait and see
     // if it is through before moving on.
+    let outcome = poll_transaction(client, &txn_hash_hex.txn_hash_hex, policy).await?;
+    Ok((response_message, outcome))
+}
 
-    let transaction_check_uri = node.get_endpoint("api/v0/get-txn");
+/// Waits for a submitted transaction to show up on `node`, following `policy`
+/// for how often to check and for how long.
+pub async fn wait_for_transaction(
+    node: &Node,
+    txn_hash_hex: &str,
+    policy: &SubmitPolicy,
+) -> Result<SubmissionOutcome, errors::DesoError> {
+    let client = DesoClient::shared(node.clone());
+    poll_transaction(&client, txn_hash_hex, policy).await
+}
+
+/// Polls the node until the transaction is found or `policy.confirmation_timeout`
+/// has passed, counting both the pauses and the time spent asking the node.
+async fn poll_transaction(
+    client: &DesoClient,
+    txn_hash_hex: &str,
+    policy: &SubmitPolicy,
+) -> Result<SubmissionOutcome, errors::DesoError> {
+    let txn_hash_hex = txn_hash_hex.to_string();
+    if policy.confirmation_timeout.is_zero() {
+        return Ok(SubmissionOutcome::Submitted { txn_hash_hex });
+    }
+    // A sleeper may not really sleep, so the pauses count even when the
+    // clock says less time has passed
+    let started = Instant::now();
+    let mut slept = Duration::ZERO;
+    let waited = |slept: Duration| slept.max(started.elapsed());
     let mut pause_count = 0;
-    while pause_count < 7 {
-        std::thread::sleep(std::time::Duration::from_secs(1 << pause_count));
-        match client
-            .post(&transaction_check_uri)
-            .json(&txn_hash_hex)
-            .send()
-            .await
-        {
-            Ok(resp) => {
-                let text = match resp.text().await {
-                    Ok(t) => t,
-                    Err(_) => {
-                        if DEBUG {
-                            println!("ERROR getting response for {}", transaction);
-                        }
-                        pause_count += 1;
-                        continue;
-                    }
-                };
-                let txn_found_struct: GetTransaction = match serde_json::from_str(&text.to_string())
-                {
-                    Ok(json) => json,
-                    Err(_) => {
-                        if DEBUG {
-                            println!("ERROR in transaction deserialzed for {}", transaction);
-                        }
-                        pause_count += 1;
-                        continue;
-                    }
-                };
-                if txn_found_struct.txn_found {
-                    return Ok(response_message);
-                } else {
-                    pause_count += 1;
-                }
+    let mut seen_in_mempool = false;
+    while waited(slept) < policy.confirmation_timeout {
+        let pause = policy
+            .backoff(pause_count)
+            .min(policy.confirmation_timeout.saturating_sub(waited(slept)));
+        client.sleeper.sleep(pause).await;
+        slept += pause;
+        pause_count += 1;
+
+        // The node reports committed transactions as in the mempool too, so
+        // one check a poll is enough until the transaction turns up
+        if !seen_in_mempool {
+            seen_in_mempool = txn_found(client, &txn_hash_hex, TxnStatus::InMempool).await;
+        }
+        if seen_in_mempool {
+            if txn_found(client, &txn_hash_hex, TxnStatus::Committed).await {
+                let block_height = get_txn_block_height(client, &txn_hash_hex).await;
+                return Ok(SubmissionOutcome::Confirmed {
+                    txn_hash_hex,
+                    block_height,
+                });
             }
-            Err(e) => {
-                if DEBUG {
-                    println!("Error for {}: {}", transaction, e);
-                }
-                pause_count += 1;
+            if !policy.await_commit {
+                return Ok(SubmissionOutcome::SeenInMempool { txn_hash_hex });
             }
-        };
+        }
+    }
+    if seen_in_mempool {
+        return Ok(SubmissionOutcome::SeenInMempool { txn_hash_hex });
+    }
+    if policy.not_found_is_error {
+        return Err(errors::DesoError::TransactionError(
+            String::from("waiting for"),
+            format!(
+                "Transaction {} not found after {:?}",
+                txn_hash_hex,
+                waited(slept)
+            ),
+        ));
+    }
+    Ok(SubmissionOutcome::TimedOut { txn_hash_hex })
+}
+
+/// Asks the node whether it has the transaction with the given status. Any
+/// failure to ask counts as not found, so the caller simply checks again later.
+async fn txn_found(client: &DesoClient, txn_hash_hex: &str, status: TxnStatus) -> bool {
+    let uri = client.node.get_endpoint("api/v0/get-txn");
+    let payload = GetTransactionRequest {
+        txn_hash_hex: txn_hash_hex.to_string(),
+        txn_status: status,
+    };
+    let resp = match client.http.post(uri).json(&payload).send().await {
+        Ok(r) => r,
+        Err(e) => {
+            if DEBUG {
+                println!("Error checking {}: {}", txn_hash_hex, e);
+            }
+            return false;
+        }
+    };
+    match resp.json::<GetTransaction>().await {
+        Ok(json) => json.txn_found,
+        Err(e) => {
+            if DEBUG {
+                println!(
+                    "ERROR in transaction deserialzed for {}: {}",
+                    txn_hash_hex, e
+                );
+            }
+            false
+        }
     }
-    Ok(response_message)
+}
+
+/// Looks up the height of the block a committed transaction landed in
+async fn get_txn_block_height(client: &DesoClient, txn_hash_hex: &str) -> Option<u64> {
+    let txn_hash = hex::decode(txn_hash_hex).ok()?;
+    let info_payload = TransactionInfoRequest {
+        transaction_id: crypto_lib::base58_check_encode(&txn_hash, client.node.network()),
+    };
+    let info: TransactionInfo = client
+        .http
+        .post(client.node.get_endpoint("api/v1/transaction-info"))
+        .json(&info_payload)
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    let block_payload = BlockRequest {
+        hash_hex: info.transactions.first()?.block_hash_hex.clone()?,
+        full_block: false,
+    };
+    let block: Block = client
+        .http
+        .post(client.node.get_endpoint("api/v1/block"))
+        .json(&block_payload)
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    Some(block.header.height)
 }
 
 async fn submit_transaction(
-    node: &Node,
+    client: &DesoClient,
     tx: &TransactionHex,
-    client: &reqwest::Client,
 ) -> Result<String, errors::DesoError> {
-    let uri = node.get_endpoint("api/v0/submit-transaction");
-    let resp = match client.post(uri).json(&tx).send().await {
+    let uri = client.node.get_endpoint("api/v0/submit-transaction");
+    let resp = match client.http.post(uri).json(&tx).send().await {
         Ok(r) => r,
         Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
     };
@@ -409,30 +916,54 @@ async fn submit_transaction(
         Ok(t) => t,
         Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
     };
-    println!("Response: {}", status);
     if status {
         Ok(raw_resp)
     } else {
+        Err(errors::DesoError::DesoError(raw_resp))
+    }
+}
+
+/// Sends a read only request to the node and parses its json response
+async fn query_node<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    what: &str,
+) -> Result<T, errors::DesoError> {
+    let resp = match request.send().await {
+        Ok(r) => r,
+        Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
+    };
+    let status: bool = resp.status().is_success();
+    let raw_resp = match resp.text().await {
+        Ok(t) => t,
+        Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
+    };
+    if !status {
         return Err(errors::DesoError::DesoError(raw_resp));
     }
+    match serde_json::from_str(&raw_resp) {
+        Ok(j) => Ok(j),
+        Err(e) => Err(errors::DesoError::JsonError(
+            String::from(what),
+            e.to_string(),
+        )),
+    }
 }
 
 async fn append_data(
-    node: &Node,
+    client: &DesoClient,
     tx: &TransactionHex,
     derived_public_key: String,
-    client: &reqwest::Client,
 ) -> Result<TransactionHex, errors::DesoError> {
-    let uri = node.get_endpoint("api/v0/append-extra-data");
+    let uri = client.node.get_endpoint("api/v0/append-extra-data");
 
     let mut extra_data: HashMap<String, String> = HashMap::new();
 
     extra_data.insert(String::from("DerivedPublicKey"), derived_public_key);
     let post_data = ExtraDataBody {
         transaction_hex: tx.transaction_hex.clone(),
-        extra_data: extra_data,
+        extra_data,
     };
-    let resp = match client.post(uri).json(&post_data).send().await {
+    let resp = match client.http.post(uri).json(&post_data).send().await {
         Ok(r) => r,
         Err(e) => return Err(errors::DesoError::ReqwestError(e.to_string())),
     };
@@ -455,6 +986,7 @@ async fn append_data(
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::sync::Arc;
 
     use super::*;
 
@@ -463,6 +995,1603 @@ mod tests {
             tokio_test::block_on($e)
         };
     }
+
+    /// Serves canned responses on a local port, answering each request with
+    /// `handler(path, body)`, and returns a `Node` pointing at it.
+    fn mock_node<F>(handler: F) -> Node
+    where
+        F: Fn(&str, &str) -> (u16, String) + Send + 'static,
+    {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split(' ').nth(1).unwrap_or("").to_string();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    let lower = line.to_lowercase();
+                    if let Some(len) = lower.strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let (status, response) = handler(&path, &String::from_utf8_lossy(&body));
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    response.len(),
+                    response
+                );
+            }
+        });
+        Node::CUSTOM(NodeConfig::new(&base_url, Network::TESTNET).unwrap())
+    }
+
+    /// Records requested waits without actually sleeping
+    #[derive(Default)]
+    struct RecordingSleeper {
+        waits: std::sync::Mutex<Vec<Duration>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Sleeper for RecordingSleeper {
+        async fn sleep(&self, duration: Duration) {
+            self.waits.lock().unwrap().push(duration);
+        }
+    }
+
+    const TEST_SEED_HEX: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn test_public_key_bytes() -> [u8; PUBLIC_KEY_LEN] {
+        let secret =
+            secp256k1::SecretKey::from_slice(&hex::decode(TEST_SEED_HEX).unwrap()).unwrap();
+        secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), &secret).serialize()
+    }
+
+    fn test_public_key() -> String {
+        crypto_lib::base58_check_encode(&test_public_key_bytes(), Network::TESTNET)
+    }
+
+    /// An unsigned version 0 post by the test key, as a node would build it
+    fn test_post_txn() -> MsgDeSoTxn {
+        let mut txn = MsgDeSoTxn::new(
+            test_public_key_bytes(),
+            TxnMeta::SubmitPost(SubmitPostMetadata {
+                body: br#"{"Body":"hello"}"#.to_vec(),
+                ..Default::default()
+            }),
+            TxnNonce {
+                expiration_block_height: 0,
+                partial_id: 0,
+            },
+        );
+        txn.version = 0;
+        txn.nonce = None;
+        txn
+    }
+
+    /// Node responses for building the version 0 `txn`: its hex, and the
+    /// signature index, which is the last byte for version 0
+    fn built_txn_response(txn: &MsgDeSoTxn) -> (String, String) {
+        let unsigned = txn.to_bytes(true).unwrap();
+        (
+            format!(r#"{{"TransactionHex":"{}"}}"#, hex::encode(&unsigned)),
+            format!(r#"{{"SignatureIndex":{}}}"#, unsigned.len() - 1),
+        )
+    }
+
+    const TEST_SUBMITTED_POST: &str = r#"{"TxnHashHex":"ab","PostEntryResponse":{"PostHashHex":"cd","PosterPublicKeyBase58Check":"tBCKTest","Body":"{}","HasUnlockable":false,"PostExtraData":{},"TimestampNanos":1}}"#;
+
+    /// A mock node for post transactions. It serves `existing_post` as the
+    /// post with hash `11..11` and builds what each submitted post asks for,
+    /// changed by `tamper`, after checking the submission with `check`. It
+    /// fails the submission `rejections` times and reports the transaction as
+    /// not found `misses` times first. Once found it is in the mempool, and
+    /// also in block 42 if `committed` is set.
+    struct PostNode {
+        existing_post: String,
+        tamper: fn(&mut MsgDeSoTxn),
+        check: fn(&serde_json::Value),
+        rejections: usize,
+        misses: usize,
+        committed: bool,
+    }
+
+    impl Default for PostNode {
+        fn default() -> Self {
+            PostNode {
+                existing_post: String::from("null"),
+                tamper: |_| {},
+                check: |_| {},
+                rejections: 0,
+                misses: 0,
+                committed: false,
+            }
+        }
+    }
+
+    impl PostNode {
+        fn start(self) -> Node {
+            let submits = std::sync::atomic::AtomicUsize::new(0);
+            let mempool_checks = std::sync::atomic::AtomicUsize::new(0);
+            let signature_index = std::sync::Mutex::new(String::new());
+            mock_node(move |path, body| match path {
+                "/api/v0/get-single-post" => {
+                    (200, format!(r#"{{"PostFound":{}}}"#, self.existing_post))
+                }
+                "/api/v0/submit-post" => {
+                    let request = serde_json::from_str(body).unwrap();
+                    (self.check)(&request);
+                    let mut txn = node_built_post(&request);
+                    (self.tamper)(&mut txn);
+                    let (built, index) = built_txn_response(&txn);
+                    *signature_index.lock().unwrap() = index;
+                    (200, built)
+                }
+                "/api/v0/signature-index" => (200, signature_index.lock().unwrap().clone()),
+                "/api/v0/submit-transaction" => {
+                    if submits.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < self.rejections {
+                        (500, String::from(r#"{"error":"busy"}"#))
+                    } else {
+                        (200, String::from(TEST_SUBMITTED_POST))
+                    }
+                }
+                "/api/v0/get-txn" => {
+                    // Like a real node, a committed transaction is also found
+                    // when asking for the mempool
+                    let found = if body.contains("Committed") {
+                        self.committed
+                            && mempool_checks.load(std::sync::atomic::Ordering::SeqCst)
+                                > self.misses
+                    } else {
+                        mempool_checks.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                            >= self.misses
+                    };
+                    (200, format!(r#"{{"TxnFound":{}}}"#, found))
+                }
+                "/api/v1/transaction-info" => (
+                    200,
+                    String::from(r#"{"Transactions":[{"BlockHashHex":"00ff"}]}"#),
+                ),
+                "/api/v1/block" => (200, String::from(r#"{"Header":{"Height":42}}"#)),
+                _ => (404, String::from("{}")),
+            })
+        }
+    }
+
+    fn test_account(node: Node) -> DesoAccount {
+        DesoAccountBuilder::new()
+            .public_key(test_public_key())
+            .seed_hex_key(String::from(TEST_SEED_HEX))
+            .node(node)
+            .build()
+            .unwrap()
+    }
+
+    fn test_post_data() -> post_lib::SubmitPostData {
+        post_lib::SubmitPostDataBuilder::new()
+            .body(String::from("hello"))
+            .public_key(test_public_key())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_confirmation_waits_use_sleeper() {
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    misses: 2,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(sleeper.clone())
+            .build()
+            .unwrap();
+
+        let submitted = aw!(client.create_post(&test_post_data())).unwrap();
+
+        assert_eq!(submitted.post_entry_response.post_hash_hex, "cd");
+        assert_eq!(
+            submitted.outcome,
+            Some(SubmissionOutcome::SeenInMempool {
+                txn_hash_hex: String::from("ab")
+            })
+        );
+        assert_eq!(
+            *sleeper.waits.lock().unwrap(),
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4)
+            ]
+        );
+    }
+    #[test]
+    fn test_custom_node_endpoint() {
+        let config = NodeConfig::new("http://localhost:17001/", Network::TESTNET).unwrap();
+        let node = Node::CUSTOM(config.clone());
+        assert_eq!(
+            node.get_endpoint("api/v0/submit-post"),
+            "http://localhost:17001/api/v0/submit-post"
+        );
+        assert_eq!(node.network(), Network::TESTNET);
+
+        let node = Node::CUSTOM(config.api_prefix("/deso/"));
+        assert_eq!(
+            node.get_endpoint("api/v0/get-txn"),
+            "http://localhost:17001/deso/api/v0/get-txn"
+        );
+
+        assert!(NodeConfig::new("not a url", Network::MAINNET).is_err());
+        assert!(NodeConfig::new("ftp://localhost", Network::MAINNET).is_err());
+
+        // Deserializing goes through the same checks
+        let config = NodeConfig::new("http://localhost:17001", Network::TESTNET)
+            .unwrap()
+            .api_prefix("deso");
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: NodeConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.base_url(), "http://localhost:17001");
+        assert_eq!(parsed.network(), Network::TESTNET);
+        assert_eq!(parsed.prefix(), Some("deso"));
+        let bad = json.replace("http://localhost:17001", "ftp://localhost");
+        assert!(serde_json::from_str::<NodeConfig>(&bad).is_err());
+    }
+
+    #[test]
+    fn test_account_keys_match_node_network() {
+        let account = test_account(Node::TEST);
+        assert_eq!(account.public_key().to_string(), test_public_key());
+        assert_eq!(account.public_key().network(), Network::TESTNET);
+
+        // A testnet key can't be used against mainnet, nor a mainnet derived key on testnet
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .public_key(test_public_key())
+                .seed_hex_key(String::from(TEST_SEED_HEX))
+                .node(Node::MAIN)
+                .build(),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+        let mainnet_key =
+            crypto_lib::base58_check_encode(&test_public_key_bytes(), Network::MAINNET);
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .public_key(test_public_key())
+                .seed_hex_key(String::from(TEST_SEED_HEX))
+                .derived_public_key(mainnet_key)
+                .node(Node::TEST)
+                .build(),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .public_key("tBCnotakey")
+                .seed_hex_key(String::from(TEST_SEED_HEX))
+                .node(Node::TEST)
+                .build(),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+        assert!(matches!(
+            post_lib::SubmitPostDataBuilder::new()
+                .body(String::from("hello"))
+                .public_key("tBCnotakey")
+                .build(),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+    }
+
+    #[test]
+    fn test_account_from_mnemonic() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let account = DesoAccountBuilder::new()
+            .mnemonic(mnemonic)
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        assert_eq!(
+            *account.seed_hex_key.expose_secret_hex(),
+            "e284129cc0922579a535bbf4d1a3b25773090d28c909bc0fed73b5e0222cc372"
+        );
+        assert_eq!(account.public_key().network(), Network::TESTNET);
+        assert_eq!(
+            hex::encode(account.public_key().as_bytes()),
+            "03aaeb52dd7494c361049de67cc680e83ebcbbbdbeb13637d92cd845f70308af5e"
+        );
+
+        // The passphrase changes the keys, and a given public key must match them
+        let with_passphrase = DesoAccountBuilder::new()
+            .mnemonic(mnemonic)
+            .passphrase("TREZOR")
+            .public_key(account.public_key().clone())
+            .node(Node::TEST)
+            .build();
+        assert!(matches!(
+            with_passphrase,
+            Err(errors::DesoError::KeyMismatch(_))
+        ));
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .mnemonic(mnemonic)
+                .seed_hex_key(String::from(TEST_SEED_HEX))
+                .build(),
+            Err(errors::DesoError::MnemonicError(_))
+        ));
+    }
+
+    #[test]
+    fn test_account_debug_hides_seed() {
+        let account = test_account(Node::TEST);
+        let shown = format!("{:?}", account);
+        assert!(shown.contains(&test_public_key()));
+        assert!(!shown.contains(TEST_SEED_HEX));
+        assert!(shown.contains("<redacted>"));
+    }
+
+    #[test]
+    fn test_account_public_key_from_seed() {
+        let account = DesoAccountBuilder::new()
+            .seed_hex_key(String::from(TEST_SEED_HEX))
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        assert_eq!(account.public_key().to_string(), test_public_key());
+
+        // A seed for someone else's key
+        let other_seed = "02".repeat(32);
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .public_key(test_public_key())
+                .seed_hex_key(other_seed.clone())
+                .node(Node::TEST)
+                .build(),
+            Err(errors::DesoError::KeyMismatch(_))
+        ));
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .seed_hex_key(String::from("not hex"))
+                .node(Node::TEST)
+                .build(),
+            Err(errors::DesoError::PrivateKeyError(_))
+        ));
+
+        // With a derived key the seed is the derived private key, so the owner has to be given
+        let derived_public_key = PrivateKey::from_hex(&other_seed)
+            .unwrap()
+            .public_key(Network::TESTNET)
+            .to_string();
+        let account = DesoAccountBuilder::new()
+            .public_key(test_public_key())
+            .seed_hex_key(other_seed.clone())
+            .derived_public_key(derived_public_key.clone())
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        assert_eq!(account.public_key().to_string(), test_public_key());
+        assert!(matches!(
+            DesoAccountBuilder::new()
+                .seed_hex_key(other_seed)
+                .derived_public_key(derived_public_key)
+                .node(Node::TEST)
+                .build(),
+            Err(errors::DesoError::DesoAccountError(_))
+        ));
+    }
+
+    #[test]
+    fn test_refuses_post_for_other_network() {
+        let client = DesoClient::new(Node::MAIN);
+        let account = test_account(Node::TEST);
+        assert!(matches!(
+            aw!(client.create_post_as(&account, &test_post_data())),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+    }
+
+    #[test]
+    fn test_submit_policy_retries_with_backoff() {
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let policy = SubmitPolicyBuilder::new()
+            .max_retries(2)
+            .backoff_base(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    rejections: 2,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(sleeper.clone())
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+
+        aw!(client.create_post(&test_post_data())).unwrap();
+
+        assert_eq!(
+            *sleeper.waits.lock().unwrap(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(100)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_confirmation_timeout_counts_round_trips() {
+        let checks = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let node_checks = checks.clone();
+        let node = mock_node(move |path, _| match path {
+            "/api/v0/get-txn" => {
+                node_checks.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(60));
+                (200, String::from(r#"{"TxnFound":false}"#))
+            }
+            _ => (404, String::from("{}")),
+        });
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let client = DesoClientBuilder::new()
+            .node(node)
+            .sleeper(sleeper.clone())
+            .build()
+            .unwrap();
+        let policy = SubmitPolicyBuilder::new()
+            .backoff_base(Duration::from_millis(10))
+            .confirmation_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let outcome = aw!(client.wait_for_transaction("ab", &policy)).unwrap();
+
+        assert_eq!(
+            outcome,
+            SubmissionOutcome::TimedOut {
+                txn_hash_hex: String::from("ab")
+            }
+        );
+        // One slow check a poll, and two polls use up the timeout
+        assert_eq!(
+            *sleeper.waits.lock().unwrap(),
+            vec![Duration::from_millis(10), Duration::from_millis(20)]
+        );
+        assert_eq!(checks.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_submit_policy_gives_up_after_retries() {
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    rejections: 2,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .submit_policy(SubmitPolicyBuilder::new().max_retries(1).build().unwrap())
+            .build()
+            .unwrap();
+
+        assert!(aw!(client.create_post(&test_post_data())).is_err());
+    }
+
+    #[test]
+    fn test_submit_policy_not_found_is_error() {
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    misses: usize::MAX,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(sleeper.clone())
+            .build()
+            .unwrap();
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::from_secs(5))
+            .not_found_is_error(true)
+            .build()
+            .unwrap();
+
+        let result = aw!(client
+            .with_submit_policy(policy)
+            .create_post(&test_post_data()));
+
+        assert!(matches!(
+            result,
+            Err(errors::DesoError::TransactionError(_, _))
+        ));
+        assert_eq!(
+            *sleeper.waits.lock().unwrap(),
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(2)
+            ]
+        );
+        // The default policy reports an unseen transaction instead of failing
+        let submitted = aw!(client.create_post(&test_post_data())).unwrap();
+        assert_eq!(
+            submitted.outcome,
+            Some(SubmissionOutcome::TimedOut {
+                txn_hash_hex: String::from("ab")
+            })
+        );
+    }
+
+    #[test]
+    fn test_outcome_confirmed_with_block_height() {
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    misses: 1,
+                    committed: true,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .build()
+            .unwrap();
+
+        let submitted = aw!(client.create_post(&test_post_data())).unwrap();
+
+        assert_eq!(
+            submitted.outcome,
+            Some(SubmissionOutcome::Confirmed {
+                txn_hash_hex: String::from("ab"),
+                block_height: Some(42)
+            })
+        );
+    }
+
+    #[test]
+    fn test_outcome_without_waiting() {
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    committed: true,
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .sleeper(sleeper.clone())
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+
+        let submitted = aw!(client.create_post(&test_post_data())).unwrap();
+
+        assert_eq!(
+            submitted.outcome,
+            Some(SubmissionOutcome::Submitted {
+                txn_hash_hex: String::from("ab")
+            })
+        );
+        assert!(sleeper.waits.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_authorize_derived_key() {
+        let derived = generate_derived_key(Network::TESTNET);
+        let owner_key = PrivateKey::from_hex(TEST_SEED_HEX).unwrap();
+        let limit = TransactionSpendingLimit::unlimited();
+        let mut authorize_txn = test_post_txn();
+        authorize_txn.meta = TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+            derived_public_key: derived.public_key.as_bytes().to_vec(),
+            expiration_block: 1000,
+            operation_type: 1,
+            access_signature: derived_key_lib::access_signature(
+                &owner_key,
+                &derived.public_key,
+                1000,
+                &limit,
+            ),
+        });
+        authorize_txn.extra_data.insert(
+            String::from(txn_lib::TRANSACTION_SPENDING_LIMIT),
+            limit.to_bytes(),
+        );
+        let (built, _) = built_txn_response(&authorize_txn);
+        let derived_public_key = derived.public_key.clone();
+        let node = mock_node(move |path, body| match path {
+            "/api/v0/authorize-derived-key" => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["OwnerPublicKeyBase58Check"], test_public_key());
+                assert_eq!(
+                    request["DerivedPublicKeyBase58Check"],
+                    derived_public_key.to_string()
+                );
+                assert_eq!(request["ExpirationBlock"], 1000);
+                assert_eq!(request["DeleteKey"], false);
+                let limit = TransactionSpendingLimit::unlimited().to_bytes();
+                assert_eq!(request["TransactionSpendingLimitHex"], hex::encode(&limit));
+
+                // The owner signed the derived key, expiration and limit
+                let mut access_bytes = derived_public_key.as_bytes().to_vec();
+                access_bytes.extend_from_slice(&1000u64.to_be_bytes());
+                access_bytes.extend_from_slice(&limit);
+                let signature = hex::decode(request["AccessSignature"].as_str().unwrap()).unwrap();
+                assert!(crypto_lib::verify_bytes(
+                    &access_bytes,
+                    &signature,
+                    &test_public_key_bytes()
+                )
+                .is_ok());
+                (200, built.clone())
+            }
+            "/api/v0/submit-transaction" => (200, String::from(r#"{"TxnHashHex":"ab"}"#)),
+            _ => (404, String::from("{}")),
+        });
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClient::new(node.clone()).with_submit_policy(policy);
+        let owner = test_account(node.clone());
+
+        let outcome = aw!(client.authorize_derived_key(
+            &owner,
+            &derived.public_key,
+            1000,
+            &TransactionSpendingLimit::unlimited()
+        ))
+        .unwrap();
+        assert_eq!(
+            outcome,
+            SubmissionOutcome::Submitted {
+                txn_hash_hex: String::from("ab")
+            }
+        );
+
+        // Only the owner's seed can make the access signature
+        let derived_account = DesoAccountBuilder::new()
+            .public_key(test_public_key())
+            .private_key(derived.private_key.clone())
+            .derived_public_key(derived.public_key.clone())
+            .node(node)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.authorize_derived_key(
+                &derived_account,
+                &derived.public_key,
+                1000,
+                &TransactionSpendingLimit::unlimited()
+            )),
+            Err(errors::DesoError::SigningError(_))
+        ));
+    }
+
+    #[test]
+    fn test_authorize_refuses_other_limit() {
+        let derived = generate_derived_key(Network::TESTNET);
+        let owner_key = PrivateKey::from_hex(TEST_SEED_HEX).unwrap();
+        let limit = TransactionSpendingLimitBuilder::new()
+            .global_deso_limit(100)
+            .build()
+            .unwrap();
+        let unlimited = TransactionSpendingLimit::unlimited();
+        let signed_txn = |signed_limit: &TransactionSpendingLimit,
+                          granted_limit: &TransactionSpendingLimit| {
+            let mut txn = test_post_txn();
+            txn.meta = TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+                derived_public_key: derived.public_key.as_bytes().to_vec(),
+                expiration_block: 1000,
+                operation_type: 1,
+                access_signature: derived_key_lib::access_signature(
+                    &owner_key,
+                    &derived.public_key,
+                    1000,
+                    signed_limit,
+                ),
+            });
+            txn.extra_data.insert(
+                String::from(txn_lib::TRANSACTION_SPENDING_LIMIT),
+                granted_limit.to_bytes(),
+            );
+            built_txn_response(&txn).0
+        };
+        // The node swaps in an unlimited grant, with or without a matching access signature
+        for built in [
+            signed_txn(&limit, &unlimited),
+            signed_txn(&unlimited, &unlimited),
+        ] {
+            let node = mock_node(move |path, _| match path {
+                "/api/v0/authorize-derived-key" => (200, built.clone()),
+                "/api/v0/submit-transaction" => (200, String::from(r#"{"TxnHashHex":"ab"}"#)),
+                _ => (404, String::from("{}")),
+            });
+            let client = DesoClient::new(node.clone());
+            let owner = test_account(node);
+
+            assert!(matches!(
+                aw!(client.authorize_derived_key(&owner, &derived.public_key, 1000, &limit)),
+                Err(errors::DesoError::TxnVerificationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_derived_key_queries_and_revoke() {
+        let derived = generate_derived_key(Network::TESTNET);
+        let unknown = generate_derived_key(Network::TESTNET);
+        let entry = format!(
+            r#"{{"OwnerPublicKeyBase58Check":"{}","DerivedPublicKeyBase58Check":"{}","ExpirationBlock":5000,"IsValid":true,"ExtraData":null,"TransactionSpendingLimit":{{"GlobalDESOLimit":90,"TransactionCountLimitMap":{{"SUBMIT_POST":3}},"IsUnlimited":false}}}}"#,
+            test_public_key(),
+            derived.public_key
+        );
+        let mut revoke_txn = test_post_txn();
+        revoke_txn.meta = TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+            derived_public_key: derived.public_key.as_bytes().to_vec(),
+            expiration_block: 5000,
+            operation_type: 0,
+            access_signature: derived_key_lib::access_signature(
+                &PrivateKey::from_hex(TEST_SEED_HEX).unwrap(),
+                &derived.public_key,
+                5000,
+                &TransactionSpendingLimit::default(),
+            ),
+        });
+        let (built, _) = built_txn_response(&revoke_txn);
+        let single_path = format!(
+            "/api/v0/get-single-derived-key/{}/{}",
+            test_public_key(),
+            derived.public_key
+        );
+        let user_keys = format!(
+            r#"{{"DerivedKeys":{{"{}":{}}}}}"#,
+            derived.public_key, entry
+        );
+        let single = format!(r#"{{"DerivedKey":{}}}"#, entry);
+        let node = mock_node(move |path, body| match path {
+            "/api/v0/get-user-derived-keys" => {
+                assert!(body.contains(&test_public_key()));
+                (200, user_keys.clone())
+            }
+            "/api/v0/authorize-derived-key" => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["DeleteKey"], true);
+                assert_eq!(request["ExpirationBlock"], 5000);
+                (200, built.clone())
+            }
+            "/api/v0/submit-transaction" => (200, String::from(r#"{"TxnHashHex":"ab"}"#)),
+            path if path == single_path => (200, single.clone()),
+            path if path.starts_with("/api/v0/get-single-derived-key/") => {
+                (200, String::from(r#"{"DerivedKey":null}"#))
+            }
+            _ => (404, String::from("{}")),
+        });
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClient::new(node.clone()).with_submit_policy(policy);
+        let owner = test_account(node);
+
+        let keys = aw!(client.get_user_derived_keys(owner.public_key())).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].derived_public_key, derived.public_key);
+        assert_eq!(keys[0].expiration_block, 5000);
+        assert!(keys[0].is_active);
+        let remaining = keys[0].remaining_limit.as_ref().unwrap();
+        assert_eq!(remaining.global_deso_limit, 90);
+        assert_eq!(
+            remaining.txn_count_limits.as_ref().unwrap()["SUBMIT_POST"],
+            3
+        );
+
+        let key = aw!(client.get_single_derived_key(owner.public_key(), &derived.public_key));
+        assert_eq!(key.unwrap().as_ref(), keys.first());
+        assert_eq!(
+            aw!(client.get_single_derived_key(owner.public_key(), &unknown.public_key)).unwrap(),
+            None
+        );
+
+        assert_eq!(
+            aw!(client.revoke_derived_key(&owner, &derived.public_key)).unwrap(),
+            SubmissionOutcome::Submitted {
+                txn_hash_hex: String::from("ab")
+            }
+        );
+        assert!(matches!(
+            aw!(client.revoke_derived_key(&owner, &unknown.public_key)),
+            Err(errors::DesoError::DesoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_revoke_refuses_authorize_txn() {
+        let derived = generate_derived_key(Network::TESTNET);
+        let entry = format!(
+            r#"{{"DerivedKey":{{"OwnerPublicKeyBase58Check":"{}","DerivedPublicKeyBase58Check":"{}","ExpirationBlock":5000,"IsValid":true,"ExtraData":null,"TransactionSpendingLimit":null}}}}"#,
+            test_public_key(),
+            derived.public_key
+        );
+        // The node turns the revoke into an authorization of the same key
+        let mut authorize_txn = test_post_txn();
+        authorize_txn.meta = TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+            derived_public_key: derived.public_key.as_bytes().to_vec(),
+            expiration_block: 5000,
+            operation_type: 1,
+            access_signature: vec![],
+        });
+        let (built, _) = built_txn_response(&authorize_txn);
+        let submitted = Arc::new(std::sync::Mutex::new(false));
+        let node_submitted = submitted.clone();
+        let node = mock_node(move |path, _| match path {
+            "/api/v0/authorize-derived-key" => (200, built.clone()),
+            "/api/v0/submit-transaction" => {
+                *node_submitted.lock().unwrap() = true;
+                (200, String::from(r#"{"TxnHashHex":"ab"}"#))
+            }
+            path if path.starts_with("/api/v0/get-single-derived-key/") => (200, entry.clone()),
+            _ => (404, String::from("{}")),
+        });
+        let client = DesoClient::new(node.clone());
+        let owner = test_account(node);
+
+        assert!(matches!(
+            aw!(client.revoke_derived_key(&owner, &derived.public_key)),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+        assert!(!*submitted.lock().unwrap());
+    }
+
+    #[test]
+    fn test_get_single_post() {
+        let post_hash_hex = "11".repeat(32);
+        let response = format!(
+            r#"{{"PostFound":{{"PostHashHex":"{}","PosterPublicKeyBase58Check":"{}","ParentStakeID":"{}","Body":"hello","ImageURLs":null,"HasUnlockable":false,"PostExtraData":null,"TimestampNanos":5,"LikeCount":3,"DiamondCount":1,"CommentCount":1,"RepostCount":2,"QuoteRepostCount":1,"ConfirmationBlockHeight":42,"Comments":[{{"PostHashHex":"{}","PosterPublicKeyBase58Check":"{}","Body":"reply","HasUnlockable":false,"PostExtraData":{{"k":"v"}},"TimestampNanos":6,"Comments":null}}],"ParentPosts":[{{"PostHashHex":"{}","PosterPublicKeyBase58Check":"{}","Body":"root","HasUnlockable":false,"PostExtraData":{{}},"TimestampNanos":4}}],"PostEntryReaderState":{{"LikedByReader":true,"DiamondLevelBestowed":0,"RepostedByReader":false,"RepostPostHashHex":""}}}}}}"#,
+            post_hash_hex,
+            test_public_key(),
+            "00".repeat(32),
+            "22".repeat(32),
+            test_public_key(),
+            "00".repeat(32),
+            test_public_key()
+        );
+        let expected_hash = post_hash_hex.clone();
+        let node = mock_node(move |path, body| match path {
+            "/api/v0/get-single-post" => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["PostHashHex"], expected_hash);
+                assert_eq!(request["ReaderPublicKeyBase58Check"], test_public_key());
+                assert_eq!(request["FetchParents"], true);
+                assert_eq!(request["CommentLimit"], 20);
+                (200, response.clone())
+            }
+            _ => (404, String::from(r#"{"error":"not found"}"#)),
+        });
+        let client = DesoClient::new(node);
+        let options = SinglePostOptions {
+            reader_public_key: Some(PublicKey::from_base58_check(&test_public_key()).unwrap()),
+            fetch_parents: true,
+            ..Default::default()
+        };
+
+        let post = aw!(client.get_single_post(&post_hash_hex, &options)).unwrap();
+        assert_eq!(post.post_hash_hex, post_hash_hex);
+        assert_eq!(post.body, "hello");
+        assert!(post.extra_data.is_empty());
+        assert_eq!(
+            (post.like_count, post.diamond_count, post.comment_count),
+            (3, 1, 1)
+        );
+        assert_eq!((post.repost_count, post.quote_repost_count), (2, 1));
+        assert_eq!(post.confirmation_block_height, 42);
+        assert_eq!(post.comments.len(), 1);
+        assert_eq!(post.comments[0].body, "reply");
+        assert_eq!(post.comments[0].extra_data["k"], "v");
+        assert_eq!(post.parent_posts.len(), 1);
+        assert_eq!(post.parent_posts[0].body, "root");
+        assert!(post.reader_state.unwrap().liked_by_reader);
+
+        for bad in ["", "not hex", "11"] {
+            assert!(matches!(
+                aw!(client.get_single_post(bad, &options)),
+                Err(errors::DesoError::PostHashError(_))
+            ));
+        }
+        let mainnet_reader = SinglePostOptions {
+            reader_public_key: Some(
+                PublicKey::from_bytes(&test_public_key_bytes(), Network::MAINNET).unwrap(),
+            ),
+            ..Default::default()
+        };
+        assert!(matches!(
+            aw!(client.get_single_post(&post_hash_hex, &mainnet_reader)),
+            Err(errors::DesoError::PublicKeyError(_))
+        ));
+    }
+
+    /// The submit post transaction an honest node builds for `request`
+    fn node_built_post(request: &serde_json::Value) -> MsgDeSoTxn {
+        let bytes = |field: &str| {
+            request[field]
+                .as_str()
+                .map(|h| hex::decode(h).unwrap())
+                .unwrap_or_default()
+        };
+        let mut txn = test_post_txn();
+        txn.meta = TxnMeta::SubmitPost(SubmitPostMetadata {
+            post_hash_to_modify: bytes("PostHashHexToModify"),
+            parent_stake_id: bytes("ParentStakeID"),
+            body: serde_json::to_vec(&request["BodyObj"]).unwrap(),
+            is_hidden: request["IsHidden"].as_bool().unwrap(),
+            ..Default::default()
+        });
+        let reposted_post_hash = bytes("RepostedPostHashHex");
+        if !reposted_post_hash.is_empty() {
+            txn.extra_data
+                .insert(String::from(REPOSTED_POST_HASH), reposted_post_hash);
+        }
+        txn
+    }
+
+    /// Makes the submit post transaction `txn` an edit of `post_hash`
+    fn modify(txn: &mut MsgDeSoTxn, post_hash: Vec<u8>) {
+        if let TxnMeta::SubmitPost(meta) = &mut txn.meta {
+            meta.post_hash_to_modify = post_hash;
+        }
+    }
+
+    fn test_existing_post(poster: &str) -> String {
+        format!(
+            r#"{{"PostHashHex":"{}","PosterPublicKeyBase58Check":"{}","Body":"hello","HasUnlockable":false,"PostExtraData":{{"nft_type":"AUTHOR","lang":"en"}},"TimestampNanos":1}}"#,
+            "11".repeat(32),
+            poster
+        )
+    }
+
+    #[test]
+    fn test_edit_post() {
+        let node = PostNode {
+            existing_post: test_existing_post(&test_public_key()),
+            check: |request| {
+                assert_eq!(request["PostHashHexToModify"], "11".repeat(32));
+                assert_eq!(request["BodyObj"]["Body"], "hello again");
+                // Unchanged extra data is kept, changed keys take the new value
+                assert_eq!(request["PostExtraData"]["nft_type"], "AUTHOR");
+                assert_eq!(request["PostExtraData"]["lang"], "fr");
+            },
+            ..Default::default()
+        }
+        .start();
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+        let mut extra_data = HashMap::new();
+        extra_data.insert(String::from("lang"), String::from("fr"));
+        let edit = post_lib::SubmitPostDataBuilder::new()
+            .body(String::from("hello again"))
+            .public_key(test_public_key())
+            .post_hash_hex_to_modify("11".repeat(32))
+            .extra_data(extra_data)
+            .build()
+            .unwrap();
+
+        let submitted = aw!(client.edit_post(&edit)).unwrap();
+        assert_eq!(submitted.outcome.unwrap().txn_hash_hex(), "ab");
+
+        // Edits need a target, a well formed one
+        assert!(matches!(
+            aw!(client.edit_post(&test_post_data())),
+            Err(errors::DesoError::SubmitPostDataBuilderError(_))
+        ));
+        assert!(matches!(
+            post_lib::SubmitPostDataBuilder::new()
+                .body(String::from("hello again"))
+                .public_key(test_public_key())
+                .post_hash_hex_to_modify(String::from("11"))
+                .build(),
+            Err(errors::DesoError::PostHashError(_))
+        ));
+
+        // A node building a new post, or editing another post, instead of the
+        // edit is refused, as is one swapping the body
+        let tampers: [fn(&mut MsgDeSoTxn); 3] = [
+            |txn| modify(txn, vec![]),
+            |txn| modify(txn, vec![0x22; HASH_LEN]),
+            |txn| {
+                if let TxnMeta::SubmitPost(meta) = &mut txn.meta {
+                    meta.body = br#"{"Body":"hello"}"#.to_vec();
+                }
+            },
+        ];
+        for tamper in tampers {
+            let client = DesoClientBuilder::new()
+                .account(test_account(
+                    PostNode {
+                        existing_post: test_existing_post(&test_public_key()),
+                        tamper,
+                        ..Default::default()
+                    }
+                    .start(),
+                ))
+                .build()
+                .unwrap();
+            assert!(matches!(
+                aw!(client.edit_post(&edit)),
+                Err(errors::DesoError::TxnVerificationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_only_poster_edits() {
+        let someone_else = PrivateKey::from_hex(&"02".repeat(32))
+            .unwrap()
+            .public_key(Network::TESTNET)
+            .to_string();
+        let node = PostNode {
+            existing_post: test_existing_post(&someone_else),
+            check: |_| panic!("Nothing should be submitted"),
+            ..Default::default()
+        }
+        .start();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .build()
+            .unwrap();
+        let edit = post_lib::SubmitPostDataBuilder::new()
+            .body(String::from("hello again"))
+            .public_key(test_public_key())
+            .post_hash_hex_to_modify("11".repeat(32))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.edit_post(&edit)),
+            Err(errors::DesoError::PostEditError(_))
+        ));
+
+        // Nor can another signer edit our post, and the node isn't asked
+        let contacted = Arc::new(std::sync::Mutex::new(false));
+        let node_contacted = contacted.clone();
+        let node = mock_node(move |_, _| {
+            *node_contacted.lock().unwrap() = true;
+            (404, String::from("{}"))
+        });
+        let client = DesoClient::new(node);
+        let other = DesoAccountBuilder::new()
+            .public_key(someone_else)
+            .seed_hex_key("02".repeat(32))
+            .node(Node::TEST)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.edit_post_as(&other, &edit)),
+            Err(errors::DesoError::PostEditError(_))
+        ));
+        assert!(!*contacted.lock().unwrap());
+    }
+
+    #[test]
+    fn test_hide_and_unhide_post() {
+        let node = PostNode {
+            existing_post: test_existing_post(&test_public_key()),
+            check: |request| {
+                // The post goes back as it was, only hidden
+                assert_eq!(request["PostHashHexToModify"], "11".repeat(32));
+                assert_eq!(request["UpdaterPublicKeyBase58Check"], test_public_key());
+                assert_eq!(request["BodyObj"]["Body"], "hello");
+                assert_eq!(request["ParentStakeID"], serde_json::Value::Null);
+                assert_eq!(request["PostExtraData"]["nft_type"], "AUTHOR");
+                assert_eq!(request["IsHidden"], true);
+            },
+            ..Default::default()
+        }
+        .start();
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClient::new(node).with_submit_policy(policy.clone());
+        let account = test_account(Node::TEST);
+
+        let submitted = aw!(client.hide_post_as(&account, &"11".repeat(32))).unwrap();
+        assert_eq!(
+            submitted.outcome,
+            Some(SubmissionOutcome::Submitted {
+                txn_hash_hex: String::from("ab")
+            })
+        );
+
+        let node = PostNode {
+            existing_post: test_existing_post(&test_public_key()),
+            check: |request| assert_eq!(request["IsHidden"], false),
+            ..Default::default()
+        }
+        .start();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+        assert!(aw!(client.unhide_post(&"11".repeat(32))).is_ok());
+
+        // A node that leaves the post visible can't pass for a hide
+        let node = PostNode {
+            existing_post: test_existing_post(&test_public_key()),
+            tamper: |txn| {
+                if let TxnMeta::SubmitPost(meta) = &mut txn.meta {
+                    meta.is_hidden = false;
+                }
+            },
+            ..Default::default()
+        }
+        .start();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.hide_post(&"11".repeat(32))),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+        assert!(matches!(
+            aw!(client.hide_post("11")),
+            Err(errors::DesoError::PostHashError(_))
+        ));
+
+        // Someone else's post can't be hidden
+        let someone_else = PrivateKey::from_hex(&"02".repeat(32))
+            .unwrap()
+            .public_key(Network::TESTNET)
+            .to_string();
+        let node = PostNode {
+            existing_post: test_existing_post(&someone_else),
+            ..Default::default()
+        }
+        .start();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.hide_post(&"11".repeat(32))),
+            Err(errors::DesoError::PostEditError(_))
+        ));
+    }
+
+    #[test]
+    fn test_repost_builder_validation() {
+        let hash = "11".repeat(32);
+        let builder = || post_lib::SubmitPostDataBuilder::new().public_key(test_public_key());
+
+        let pure = builder().repost(hash.clone()).build().unwrap();
+        assert_eq!(pure.reposted_post_hash_hex, Some(hash.clone()));
+        assert_eq!(pure.body_obj.body, "");
+        let quote = builder()
+            .quote_repost(hash.clone())
+            .body(String::from("look at this"))
+            .build()
+            .unwrap();
+        assert_eq!(quote.body_obj.body, "look at this");
+
+        // Quotes need a body, plain posts still do too
+        for quote in [
+            builder().quote_repost(hash.clone()),
+            builder().quote_repost(hash.clone()).body(String::new()),
+            builder(),
+        ] {
+            assert!(matches!(
+                quote.build(),
+                Err(errors::DesoError::SubmitPostDataBuilderError(_))
+            ));
+        }
+        // Pure reposts carry nothing of their own
+        for pure in [
+            builder().repost(hash.clone()).body(String::from("hi")),
+            builder()
+                .repost(hash.clone())
+                .image_urls(vec![String::from("https://images.deso.org/a.png")]),
+            builder()
+                .repost(hash.clone())
+                .video_urls(vec![String::from("https://video.deso.org/a")]),
+        ] {
+            assert!(matches!(
+                pure.build(),
+                Err(errors::DesoError::InvalidPostData(_))
+            ));
+        }
+        assert!(matches!(
+            builder().repost(String::from("11")).build(),
+            Err(errors::DesoError::PostHashError(_))
+        ));
+    }
+
+    #[test]
+    fn test_repost_and_quote_repost() {
+        let node = PostNode {
+            check: |request| {
+                assert_eq!(request["RepostedPostHashHex"], "11".repeat(32));
+                assert_eq!(request["UpdaterPublicKeyBase58Check"], test_public_key());
+                assert!(
+                    request["BodyObj"]["Body"] == "" || request["BodyObj"]["Body"] == "so true"
+                );
+            },
+            ..Default::default()
+        }
+        .start();
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+
+        assert!(aw!(client.repost(&"11".repeat(32))).is_ok());
+        assert!(aw!(client.quote_repost(&"11".repeat(32), String::from("so true"))).is_ok());
+        assert!(matches!(
+            aw!(client.quote_repost(&"11".repeat(32), String::new())),
+            Err(errors::DesoError::SubmitPostDataBuilderError(_))
+        ));
+
+        // The node has to build a repost of the post asked for
+        let client = DesoClientBuilder::new()
+            .account(test_account(
+                PostNode {
+                    tamper: |txn| {
+                        txn.extra_data.remove(REPOSTED_POST_HASH);
+                    },
+                    ..Default::default()
+                }
+                .start(),
+            ))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.repost(&"11".repeat(32))),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_like_post() {
+        let mut like_txn = test_post_txn();
+        like_txn.meta = TxnMeta::Like(LikeMetadata {
+            liked_post_hash: [0x11; HASH_LEN],
+            is_unlike: true,
+        });
+        let (built, _) = built_txn_response(&like_txn);
+        let post = format!(
+            r#"{{"PostFound":{{"PostHashHex":"{}","PosterPublicKeyBase58Check":"{}","Body":"hello","HasUnlockable":false,"PostExtraData":null,"TimestampNanos":1,"LikeCount":1,"PostEntryReaderState":{{"LikedByReader":true}}}}}}"#,
+            "11".repeat(32),
+            test_public_key()
+        );
+        let node = mock_node(move |path, body| match path {
+            "/api/v0/create-like-stateless" => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["ReaderPublicKeyBase58Check"], test_public_key());
+                assert_eq!(request["IsUnlike"], true);
+                assert_eq!(request["MinFeeRateNanosPerKB"], DEFAULT_FEE_RATE);
+                (200, built.clone())
+            }
+            "/api/v0/get-single-post" => {
+                let request: serde_json::Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["ReaderPublicKeyBase58Check"], test_public_key());
+                (200, post.clone())
+            }
+            "/api/v0/submit-transaction" => (200, String::from(r#"{"TxnHashHex":"ab"}"#)),
+            _ => (404, String::from("{}")),
+        });
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::ZERO)
+            .build()
+            .unwrap();
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .submit_policy(policy)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            aw!(client.like_post(&"11".repeat(32), true)).unwrap(),
+            SubmissionOutcome::Submitted {
+                txn_hash_hex: String::from("ab")
+            }
+        );
+        // The node built a like of post 11..11, not of this one
+        assert!(matches!(
+            aw!(client.like_post(&"22".repeat(32), true)),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+        assert!(matches!(
+            aw!(client.like_post("22", false)),
+            Err(errors::DesoError::PostHashError(_))
+        ));
+
+        let reader = client.account().unwrap().public_key().clone();
+        assert!(aw!(client.has_liked_post(&reader, &"11".repeat(32))).unwrap());
+    }
+
+    #[test]
+    fn test_like_post_refuses_flipped_like() {
+        // The node builds an unlike whatever it was asked for
+        let mut unlike_txn = test_post_txn();
+        unlike_txn.meta = TxnMeta::Like(LikeMetadata {
+            liked_post_hash: [0x11; HASH_LEN],
+            is_unlike: true,
+        });
+        let (built, _) = built_txn_response(&unlike_txn);
+        let node = mock_node(move |path, _| match path {
+            "/api/v0/create-like-stateless" => (200, built.clone()),
+            "/api/v0/submit-transaction" => (200, String::from(r#"{"TxnHashHex":"ab"}"#)),
+            _ => (404, String::from("{}")),
+        });
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            aw!(client.like_post(&"11".repeat(32), false)),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_wait_for_transaction_await_commit() {
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let client = DesoClientBuilder::new()
+            .node(PostNode::default().start())
+            .sleeper(sleeper.clone())
+            .build()
+            .unwrap();
+        let policy = SubmitPolicyBuilder::new()
+            .confirmation_timeout(Duration::from_secs(3))
+            .await_commit(true)
+            .build()
+            .unwrap();
+
+        let outcome = aw!(client.wait_for_transaction("ab", &policy)).unwrap();
+
+        assert_eq!(
+            outcome,
+            SubmissionOutcome::SeenInMempool {
+                txn_hash_hex: String::from("ab")
+            }
+        );
+        assert_eq!(sleeper.waits.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_submit_locally_built_transaction() {
+        let client = DesoClientBuilder::new()
+            .node(PostNode::default().start())
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .build()
+            .unwrap();
+        let mut txn = MsgDeSoTxn::new(
+            [2; PUBLIC_KEY_LEN],
+            TxnMeta::Like(LikeMetadata {
+                liked_post_hash: [1; HASH_LEN],
+                is_unlike: false,
+            }),
+            TxnNonce {
+                expiration_block_height: 10,
+                partial_id: 1,
+            },
+        );
+
+        assert!(aw!(client.submit_signed_transaction(&txn)).is_err());
+
+        txn.sign(&PrivateKey::from_hex(TEST_SEED_HEX).unwrap())
+            .unwrap();
+        let outcome = aw!(client.submit_signed_transaction(&txn)).unwrap();
+        assert_eq!(outcome.txn_hash_hex(), "ab");
+    }
+
+    #[test]
+    fn test_refuses_to_sign_unexpected_transaction() {
+        // The node can't make the post for someone else, make it pay out, turn
+        // it into an edit of another post, a comment or a repost, or swap its body
+        let tampers: [fn(&mut MsgDeSoTxn); 6] = [
+            |txn| txn.public_key = vec![3; PUBLIC_KEY_LEN],
+            |txn| {
+                txn.outputs.push(DesoOutput {
+                    public_key: [3; PUBLIC_KEY_LEN],
+                    amount_nanos: 1000,
+                })
+            },
+            |txn| modify(txn, vec![0x22; HASH_LEN]),
+            |txn| {
+                if let TxnMeta::SubmitPost(meta) = &mut txn.meta {
+                    meta.parent_stake_id = vec![0x22; HASH_LEN];
+                }
+            },
+            |txn| {
+                txn.extra_data
+                    .insert(String::from(REPOSTED_POST_HASH), vec![0x22; HASH_LEN]);
+            },
+            |txn| {
+                if let TxnMeta::SubmitPost(meta) = &mut txn.meta {
+                    meta.body = br#"{"Body":"buy my coin"}"#.to_vec();
+                }
+            },
+        ];
+        for tamper in tampers {
+            let client = DesoClientBuilder::new()
+                .account(test_account(
+                    PostNode {
+                        tamper,
+                        ..Default::default()
+                    }
+                    .start(),
+                ))
+                .build()
+                .unwrap();
+            assert!(matches!(
+                aw!(client.create_post(&test_post_data())),
+                Err(errors::DesoError::TxnVerificationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_custom_txn_verifier() {
+        let verifier: TxnVerifier = Arc::new(|txn: &MsgDeSoTxn| match &txn.meta {
+            TxnMeta::SubmitPost(meta) if meta.body.len() < 10 => Ok(()),
+            _ => Err(errors::DesoError::TxnVerificationError(String::from(
+                "Body too long",
+            ))),
+        });
+        let client = DesoClientBuilder::new()
+            .account(test_account(PostNode::default().start()))
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .txn_verifier(verifier)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.create_post(&test_post_data())),
+            Err(errors::DesoError::TxnVerificationError(_))
+        ));
+    }
+
+    /// Signs with a key it holds, the way a remote signer would, counting
+    /// the transactions it was asked to sign
+    struct CountingSigner {
+        public_key: PublicKey,
+        private_key: PrivateKey,
+        signed: std::sync::Mutex<Vec<Vec<u8>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Signer for CountingSigner {
+        fn public_key(&self) -> &PublicKey {
+            &self.public_key
+        }
+
+        fn derived_public_key(&self) -> Option<&PublicKey> {
+            None
+        }
+
+        async fn sign_transaction(
+            &self,
+            unsigned_txn: &[u8],
+        ) -> Result<Vec<u8>, errors::DesoError> {
+            self.signed.lock().unwrap().push(unsigned_txn.to_vec());
+            Ok(crypto_lib::sign_bytes(unsigned_txn, &self.private_key))
+        }
+    }
+
+    #[test]
+    fn test_custom_signer() {
+        let signer = Arc::new(CountingSigner {
+            public_key: PublicKey::from_base58_check(&test_public_key()).unwrap(),
+            private_key: PrivateKey::from_hex(TEST_SEED_HEX).unwrap(),
+            signed: Default::default(),
+        });
+        let client = DesoClientBuilder::new()
+            .node(PostNode::default().start())
+            .signer(signer.clone())
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .build()
+            .unwrap();
+        assert!(client.account().is_none());
+
+        let submitted = aw!(client.create_post(&test_post_data())).unwrap();
+        assert_eq!(submitted.post_entry_response.post_hash_hex, "cd");
+        // The signer got the post the node built, unsigned
+        let signed = signer.signed.lock().unwrap().clone();
+        assert_eq!(signed.len(), 1);
+        let txn = MsgDeSoTxn::from_bytes(&signed[0]).unwrap();
+        assert_eq!(txn.public_key, test_public_key_bytes());
+        assert!(txn.signature.is_none());
+
+        // A signer holding the wrong key is caught before anything is submitted
+        let wrong_key = CountingSigner {
+            public_key: PublicKey::from_base58_check(&test_public_key()).unwrap(),
+            private_key: PrivateKey::from_hex(&"02".repeat(32)).unwrap(),
+            signed: Default::default(),
+        };
+        assert!(matches!(
+            aw!(client.create_post_as(&wrong_key, &test_post_data())),
+            Err(errors::DesoError::VerificationError(_))
+        ));
+
+        // Another account's signer can't publish our post data
+        let other_key = PrivateKey::from_hex(&"02".repeat(32)).unwrap();
+        let other_account = CountingSigner {
+            public_key: other_key.public_key(Network::TESTNET),
+            private_key: other_key,
+            signed: Default::default(),
+        };
+        assert!(matches!(
+            aw!(client.create_post_as(&other_account, &test_post_data())),
+            Err(errors::DesoError::SignerMismatch(_))
+        ));
+        assert!(other_account.signed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_signature_index_computed_locally() {
+        let (built, _) = built_txn_response(&test_post_txn());
+        let node = mock_node(move |path, _| match path {
+            "/api/v0/submit-post" => (200, built.clone()),
+            "/api/v0/submit-transaction" => (200, String::from(TEST_SUBMITTED_POST)),
+            "/api/v0/get-txn" => (200, String::from(r#"{"TxnFound":true}"#)),
+            _ => (404, String::from("{}")),
+        });
+
+        let client = DesoClientBuilder::new()
+            .account(test_account(node.clone()))
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .build()
+            .unwrap();
+        assert!(aw!(client.create_post(&test_post_data())).is_ok());
+
+        // This node has no signature-index endpoint to fall back on
+        let client = DesoClientBuilder::new()
+            .account(test_account(node))
+            .sleeper(Arc::new(RecordingSleeper::default()))
+            .remote_signature_index(true)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            aw!(client.create_post(&test_post_data())),
+            Err(errors::DesoError::SigningError(_))
+        ));
+    }
+
     #[test]
     fn test_create_post() {
         dotenv::from_filename("src/.env").ok();
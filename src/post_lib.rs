@@ -3,26 +3,91 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::errors;
+use crate::key_lib::PublicKey;
+use crate::submit_lib::SubmissionOutcome;
+use crate::txn_lib::{PostExpectations, HASH_LEN};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PostEntryResponse {
     #[serde(rename = "PostHashHex")]
     pub post_hash_hex: String, // Hex of the Post Hash. Used as the unique identifier of this post.
     #[serde(rename = "PosterPublicKeyBase58Check")]
     pub poster_public_key: String,
+    /// The post hash hex of the post this one comments on, empty for top level posts
+    #[serde(rename = "ParentStakeID", default)]
+    pub parent_post_hash_hex: String,
     #[serde(rename = "Body")]
     pub body: String,
     #[serde(rename = "ImageURLs")]
     pub image_urls: Option<Vec<String>>,
+    #[serde(rename = "VideoURLs", default)]
+    pub video_urls: Option<Vec<String>>,
+    /// The post this one reposts, if it is a repost
+    #[serde(rename = "RepostedPostEntryResponse", default)]
+    pub reposted_post: Option<Box<PostEntryResponse>>,
+    #[serde(rename = "IsHidden", default)]
+    pub is_hidden: bool,
     #[serde(rename = "HasUnlockable")]
     pub has_unlockable: bool,
-    #[serde(rename = "PostExtraData")]
+    #[serde(
+        rename = "PostExtraData",
+        default,
+        deserialize_with = "null_as_default"
+    )]
     pub extra_data: HashMap<String, String>,
     #[serde(rename = "NumNFTCopies")]
     #[serde(default)]
     pub copies_minted: u64,
     #[serde(rename = "TimestampNanos")]
     pub timestamp: u128,
+    /// Height of the block the post was mined in, 0 while it is in the mempool
+    #[serde(rename = "ConfirmationBlockHeight", default)]
+    pub confirmation_block_height: u64,
+    #[serde(rename = "InMempool", default)]
+    pub in_mempool: bool,
+    #[serde(rename = "LikeCount", default)]
+    pub like_count: u64,
+    #[serde(rename = "DiamondCount", default)]
+    pub diamond_count: u64,
+    #[serde(rename = "CommentCount", default)]
+    pub comment_count: u64,
+    #[serde(rename = "RepostCount", default)]
+    pub repost_count: u64,
+    #[serde(rename = "QuoteRepostCount", default)]
+    pub quote_repost_count: u64,
+    /// Comments on the post, only filled in by `get_single_post`
+    #[serde(rename = "Comments", default, deserialize_with = "null_as_default")]
+    pub comments: Vec<PostEntryResponse>,
+    /// The posts above this one in its thread, top first, only filled in by
+    /// `get_single_post` when asked to fetch parents
+    #[serde(rename = "ParentPosts", default, deserialize_with = "null_as_default")]
+    pub parent_posts: Vec<PostEntryResponse>,
+    /// What the reader did with the post, when a reader was given
+    #[serde(rename = "PostEntryReaderState", default)]
+    pub reader_state: Option<PostEntryReaderState>,
+}
+
+/// How the reader of a post has interacted with it
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostEntryReaderState {
+    #[serde(rename = "LikedByReader", default)]
+    pub liked_by_reader: bool,
+    #[serde(rename = "DiamondLevelBestowed", default)]
+    pub diamond_level_bestowed: i64,
+    #[serde(rename = "RepostedByReader", default)]
+    pub reposted_by_reader: bool,
+    /// The post hash hex of the reader's repost, if they reposted it
+    #[serde(rename = "RepostPostHashHex", default)]
+    pub repost_post_hash_hex: String,
+}
+
+/// The node sends `null` for empty maps and lists
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,14 +100,18 @@ pub struct SinglePostResponse {
 pub struct SubmittedTransaction {
     #[serde(rename = "PostEntryResponse")]
     pub post_entry_response: PostEntryResponse,
+
+    /// How far the transaction got, always set on transactions returned by the SDK
+    #[serde(skip)]
+    pub outcome: Option<SubmissionOutcome>,
 }
 
 /// The main data to post a new post on Deso
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SubmitPostData {
     /// Public key of the user making a new post, editing a post, or making a comment
     #[serde(rename = "UpdaterPublicKeyBase58Check")]
-    pub public_key: String,
+    pub public_key: PublicKey,
 
     /// Only used if making a comment. The post hash hex of the post your commenting on.
     #[serde(rename = "ParentStakeID")]
@@ -52,9 +121,17 @@ pub struct SubmitPostData {
     #[serde(rename = "BodyObj")]
     pub body_obj: SubmitPostBodyObject,
 
-    /// Min fee rate nanos per kb, defaults to 1250
+    /// Min fee rate nanos per kb, defaults to the client's fee rate (1250 unless configured)
     #[serde(rename = "MinFeeRateNanosPerKB")]
-    pub fee_rate: u64,
+    pub fee_rate: Option<u64>,
+
+    /// Only used when editing a post. The post hash hex of the post being edited.
+    #[serde(rename = "PostHashHexToModify")]
+    pub post_hash_hex_to_modify: Option<String>,
+
+    /// Only used when reposting. The post hash hex of the post being reposted.
+    #[serde(rename = "RepostedPostHashHex")]
+    pub reposted_post_hash_hex: Option<String>,
 
     /// Used to "delete" a post. Defaults to false.
     #[serde(rename = "IsHidden")]
@@ -65,6 +142,64 @@ pub struct SubmitPostData {
     pub extra_data: Option<HashMap<String, String>>,
 }
 
+impl SubmitPostData {
+    /// The submit post data that recreates `post` as it is, for resubmitting
+    /// it as an edit
+    pub(crate) fn from_existing(post: &PostEntryResponse) -> Result<Self, errors::DesoError> {
+        let parent_post_hash_hex = if post.parent_post_hash_hex.is_empty() {
+            None
+        } else {
+            Some(post.parent_post_hash_hex.clone())
+        };
+        Ok(SubmitPostData {
+            public_key: PublicKey::from_base58_check(&post.poster_public_key)?,
+            parent_post_hash_hex,
+            post_hash_hex_to_modify: Some(post.post_hash_hex.clone()),
+            reposted_post_hash_hex: post
+                .reposted_post
+                .as_ref()
+                .map(|reposted| reposted.post_hash_hex.clone()),
+            body_obj: SubmitPostBodyObject {
+                body: post.body.clone(),
+                image_urls: post.image_urls.clone(),
+                video_urls: post.video_urls.clone(),
+            },
+            fee_rate: None,
+            is_hidden: post.is_hidden,
+            extra_data: None,
+        })
+    }
+
+    /// What the submit post transaction the node builds for this data must carry
+    pub(crate) fn expectations(&self) -> Result<PostExpectations, errors::DesoError> {
+        let parse = |post_hash_hex: &Option<String>| match post_hash_hex {
+            Some(post_hash_hex) => parse_post_hash_hex(post_hash_hex).map(Some),
+            None => Ok(None),
+        };
+        let parent_stake_id = match &self.parent_post_hash_hex {
+            Some(parent) => match hex::decode(parent) {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    return Err(errors::DesoError::PostHashError(format!(
+                        "{}: {}",
+                        parent, e
+                    )))
+                }
+            },
+            None => None,
+        };
+        Ok(PostExpectations {
+            post_hash_to_modify: parse(&self.post_hash_hex_to_modify)?,
+            parent_stake_id,
+            reposted_post_hash: parse(&self.reposted_post_hash_hex)?,
+            body: self.body_obj.body.clone(),
+            image_urls: self.body_obj.image_urls.clone().unwrap_or_default(),
+            video_urls: self.body_obj.video_urls.clone().unwrap_or_default(),
+            is_hidden: self.is_hidden,
+        })
+    }
+}
+
 /// Builder for building a submit post data
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SubmitPostDataBuilder {
@@ -74,6 +209,15 @@ pub struct SubmitPostDataBuilder {
     /// Only used if making a comment. The post hash hex of the post your commenting on.
     pub parent_post_hash_hex: Option<String>,
 
+    /// Only used when editing a post. The post hash hex of the post being edited.
+    pub post_hash_hex_to_modify: Option<String>,
+
+    /// Only used when reposting. The post hash hex of the post being reposted.
+    pub reposted_post_hash_hex: Option<String>,
+
+    /// Whether the repost quotes the post with a body of its own
+    pub is_quote_repost: Option<bool>,
+
     /// The body of your post
     pub body: Option<String>,
 
@@ -83,7 +227,7 @@ pub struct SubmitPostDataBuilder {
     /// Any and all videos for the post
     pub video_urls: Option<Vec<String>>,
 
-    /// Min fee rate nanos per kb, defaults to 1250
+    /// Min fee rate nanos per kb, defaults to the client's fee rate (1250 unless configured)
     pub fee_rate: Option<u64>,
 
     /// Used to "delete" a post, defaults to false
@@ -93,23 +237,32 @@ pub struct SubmitPostDataBuilder {
     pub extra_data: Option<HashMap<String, String>>,
 }
 
+impl Default for SubmitPostDataBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(dead_code)]
 impl SubmitPostDataBuilder {
     pub fn new() -> Self {
         SubmitPostDataBuilder {
             public_key: None,
             parent_post_hash_hex: None,
+            post_hash_hex_to_modify: None,
+            reposted_post_hash_hex: None,
+            is_quote_repost: None,
             body: None,
             image_urls: None,
             video_urls: None,
-            fee_rate: Some(1250),
+            fee_rate: None,
             is_hidden: Some(false),
             extra_data: None,
         }
     }
-    /// Public key of the user making a new post, editing a post, or making a comment
-    pub fn public_key(mut self, public_key: String) -> Self {
-        self.public_key = Some(public_key);
+    /// Public key of the user making a new post, editing a post, or making a comment, in Base58Check
+    pub fn public_key(mut self, public_key: impl Into<String>) -> Self {
+        self.public_key = Some(public_key.into());
         self
     }
     /// Only used if making a comment. The post hash hex of the post your commenting on.
@@ -117,6 +270,23 @@ impl SubmitPostDataBuilder {
         self.parent_post_hash_hex = Some(post_hash_hex);
         self
     }
+    /// Only used when editing a post. The post hash hex of the post being edited.
+    pub fn post_hash_hex_to_modify(mut self, post_hash_hex: String) -> Self {
+        self.post_hash_hex_to_modify = Some(post_hash_hex);
+        self
+    }
+    /// Reposts the post with hash `post_hash_hex`. A pure repost has no body or media.
+    pub fn repost(mut self, post_hash_hex: String) -> Self {
+        self.reposted_post_hash_hex = Some(post_hash_hex);
+        self.is_quote_repost = Some(false);
+        self
+    }
+    /// Quotes the post with hash `post_hash_hex`. A quote repost needs a body.
+    pub fn quote_repost(mut self, post_hash_hex: String) -> Self {
+        self.reposted_post_hash_hex = Some(post_hash_hex);
+        self.is_quote_repost = Some(true);
+        self
+    }
     /// The body of your post
     pub fn body(mut self, body: String) -> Self {
         self.body = Some(body);
@@ -132,7 +302,7 @@ impl SubmitPostDataBuilder {
         self.video_urls = Some(video_urls);
         self
     }
-    /// Min fee rate nanos per kb, defaults to 1250
+    /// Min fee rate nanos per kb, defaults to the client's fee rate (1250 unless configured)
     pub fn fee_rate(mut self, fee_rate: u64) -> Self {
         self.fee_rate = Some(fee_rate);
         self
@@ -149,7 +319,27 @@ impl SubmitPostDataBuilder {
     }
     /// Builds the SubmitPostData
     pub fn build(self) -> Result<SubmitPostData, errors::DesoError> {
-        if self.body.is_none() {
+        let is_pure_repost =
+            self.reposted_post_hash_hex.is_some() && self.is_quote_repost != Some(true);
+        if is_pure_repost {
+            let has_body = self.body.as_ref().is_some_and(|body| !body.is_empty());
+            let has_media = self
+                .image_urls
+                .as_ref()
+                .is_some_and(|urls| !urls.is_empty())
+                || self
+                    .video_urls
+                    .as_ref()
+                    .is_some_and(|urls| !urls.is_empty());
+            if has_body || has_media {
+                return Err(errors::DesoError::InvalidPostData(String::from(
+                    "A pure repost can't have a body, images or videos, quote repost instead",
+                )));
+            }
+        } else if self.body.is_none()
+            || (self.reposted_post_hash_hex.is_some() && self.body.as_deref() == Some(""))
+        {
+            // Posts and quote reposts need a body
             return Err(errors::DesoError::SubmitPostDataBuilderError(String::from(
                 "Body",
             )));
@@ -159,16 +349,24 @@ impl SubmitPostDataBuilder {
                 "Poster Public Key",
             )));
         }
+        if let Some(post_hash_hex) = &self.post_hash_hex_to_modify {
+            parse_post_hash_hex(post_hash_hex)?;
+        }
+        if let Some(post_hash_hex) = &self.reposted_post_hash_hex {
+            parse_post_hash_hex(post_hash_hex)?;
+        }
         let body_object = SubmitPostBodyObject {
-            body: self.body.unwrap(),
+            body: self.body.unwrap_or_default(),
             image_urls: self.image_urls,
             video_urls: self.video_urls,
         };
         Ok(SubmitPostData {
-            public_key: self.public_key.unwrap(),
+            public_key: PublicKey::from_base58_check(&self.public_key.unwrap())?,
             parent_post_hash_hex: self.parent_post_hash_hex,
+            post_hash_hex_to_modify: self.post_hash_hex_to_modify,
+            reposted_post_hash_hex: self.reposted_post_hash_hex,
             body_obj: body_object,
-            fee_rate: self.fee_rate.unwrap(),
+            fee_rate: self.fee_rate,
             is_hidden: self.is_hidden.unwrap(),
             extra_data: self.extra_data,
         })
@@ -180,12 +378,83 @@ pub struct GetSinglePost {
     #[serde(rename = "PostHashHex")]
     pub post_hash_hex: String,
 
+    #[serde(rename = "ReaderPublicKeyBase58Check")]
+    pub reader_public_key: Option<PublicKey>,
+
+    #[serde(rename = "FetchParents")]
+    pub fetch_parents: bool,
+
+    #[serde(rename = "CommentOffset")]
+    pub comment_offset: u32,
+
     #[serde(rename = "CommentLimit")]
     pub comment_limit: u32,
 }
 
-///Body of a Deso post, includes the string content and any images(optional) or videos(optional)
 #[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct CreateLikeRequest {
+    #[serde(rename = "ReaderPublicKeyBase58Check")]
+    pub reader_public_key: PublicKey,
+
+    #[serde(rename = "LikedPostHashHex")]
+    pub liked_post_hash_hex: String,
+
+    #[serde(rename = "IsUnlike")]
+    pub is_unlike: bool,
+
+    #[serde(rename = "MinFeeRateNanosPerKB")]
+    pub fee_rate: u64,
+}
+
+/// What to fetch along with a post in `get_single_post`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinglePostOptions {
+    /// Fills in the post's reader state for this account
+    pub reader_public_key: Option<PublicKey>,
+    /// Also fetch the posts above this one in its thread
+    pub fetch_parents: bool,
+    /// How many comments to skip
+    pub comment_offset: u32,
+    /// How many comments to fetch, defaults to 20
+    pub comment_limit: u32,
+}
+
+impl Default for SinglePostOptions {
+    fn default() -> Self {
+        SinglePostOptions {
+            reader_public_key: None,
+            fetch_parents: false,
+            comment_offset: 0,
+            comment_limit: 20,
+        }
+    }
+}
+
+/// The post hash `post_hash_hex` is the hex of
+pub(crate) fn parse_post_hash_hex(
+    post_hash_hex: &str,
+) -> Result<[u8; HASH_LEN], errors::DesoError> {
+    match hex::decode(post_hash_hex) {
+        Ok(bytes) if bytes.len() == HASH_LEN => {
+            let mut hash = [0u8; HASH_LEN];
+            hash.copy_from_slice(&bytes);
+            Ok(hash)
+        }
+        Ok(bytes) => Err(errors::DesoError::PostHashError(format!(
+            "{} is {} bytes, expected {}",
+            post_hash_hex,
+            bytes.len(),
+            HASH_LEN
+        ))),
+        Err(e) => Err(errors::DesoError::PostHashError(format!(
+            "{}: {}",
+            post_hash_hex, e
+        ))),
+    }
+}
+
+///Body of a Deso post, includes the string content and any images(optional) or videos(optional)
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SubmitPostBodyObject {
     #[serde(rename = "Body")]
     pub body: String,
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+
+use crate::crypto_lib;
+use crate::errors;
+use crate::key_lib::PublicKey;
+use crate::DesoAccount;
+
+/// Signs transactions for an account.
+///
+/// `DesoAccount` is the default, in-memory signer. Implement this to keep
+/// keys outside the process, e.g. in a remote signing service or a local
+/// daemon, or to stand in for the account in tests.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The public key of the account transactions are signed for
+    fn public_key(&self) -> &PublicKey;
+
+    /// The derived public key the signer signs with, if it isn't the owner key
+    fn derived_public_key(&self) -> Option<&PublicKey>;
+
+    /// Signs the bytes of an unsigned transaction, returning the DER
+    /// signature over their double sha256
+    async fn sign_transaction(&self, unsigned_txn: &[u8]) -> Result<Vec<u8>, errors::DesoError>;
+
+    /// The key transaction signatures are checked against: the derived
+    /// public key if there is one, else the owner's
+    fn signing_public_key(&self) -> &PublicKey {
+        self.derived_public_key()
+            .unwrap_or_else(|| self.public_key())
+    }
+}
+
+#[async_trait]
+impl Signer for DesoAccount {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn derived_public_key(&self) -> Option<&PublicKey> {
+        self.derived_public_key.as_ref()
+    }
+
+    async fn sign_transaction(&self, unsigned_txn: &[u8]) -> Result<Vec<u8>, errors::DesoError> {
+        Ok(crypto_lib::sign_bytes(unsigned_txn, &self.seed_hex_key))
+    }
+}
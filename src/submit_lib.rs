@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use secp256k1::rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::errors;
+
+/// Waits between submission retries and confirmation checks.
+///
+/// The SDK never blocks the executor thread while waiting. Swap in your own
+/// implementation to run on a different timer or to skip waits in tests.
+#[async_trait]
+pub trait Sleeper: Send + Sync {
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The default sleeper, backed by `tokio::time::sleep`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioSleeper;
+
+#[async_trait]
+impl Sleeper for TokioSleeper {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// How far a submitted transaction got by the time the SDK stopped waiting
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionOutcome {
+    /// Accepted by the node, without waiting to see it afterwards
+    Submitted { txn_hash_hex: String },
+    /// Found in the node's mempool but not yet in a block
+    SeenInMempool { txn_hash_hex: String },
+    /// Included in a block. The height is `None` if the node could not report it.
+    Confirmed {
+        txn_hash_hex: String,
+        block_height: Option<u64>,
+    },
+    /// Accepted by the node, but never found before the confirmation timeout
+    TimedOut { txn_hash_hex: String },
+}
+
+impl SubmissionOutcome {
+    /// Hex of the transaction hash
+    pub fn txn_hash_hex(&self) -> &str {
+        match self {
+            SubmissionOutcome::Submitted { txn_hash_hex }
+            | SubmissionOutcome::SeenInMempool { txn_hash_hex }
+            | SubmissionOutcome::Confirmed { txn_hash_hex, .. }
+            | SubmissionOutcome::TimedOut { txn_hash_hex } => txn_hash_hex,
+        }
+    }
+}
+
+/// Controls how transactions are retried on submission and how long the SDK
+/// waits for the node to report them afterwards.
+///
+/// The default matches the SDK's original behaviour: a single submission
+/// attempt, then up to seven checks spaced 1, 2, 4 ... 64 seconds apart that
+/// stop as soon as the node has the transaction, and no error if it never does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmitPolicy {
+    pub(crate) max_retries: u8,
+    pub(crate) backoff_base: Duration,
+    pub(crate) backoff_max: Duration,
+    pub(crate) jitter: f64,
+    pub(crate) confirmation_timeout: Duration,
+    pub(crate) not_found_is_error: bool,
+    pub(crate) await_commit: bool,
+}
+
+impl Default for SubmitPolicy {
+    fn default() -> Self {
+        SubmitPolicy {
+            max_retries: 0,
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(64),
+            jitter: 0.0,
+            confirmation_timeout: Duration::from_secs(127),
+            not_found_is_error: false,
+            await_commit: false,
+        }
+    }
+}
+
+impl SubmitPolicy {
+    /// The wait before the `attempt`th retry or check: `base * 2^attempt`,
+    /// capped at the max and then shortened by up to `jitter` of itself.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .backoff_base
+            .checked_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .unwrap_or(self.backoff_max);
+        let delay = exponential.min(self.backoff_max);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        let roll: f64 = secp256k1::rand::thread_rng().gen();
+        delay.mul_f64(1.0 - self.jitter * roll)
+    }
+}
+
+/// Builder for a `SubmitPolicy`
+pub struct SubmitPolicyBuilder {
+    /// How many times to resubmit after a failed submission, defaults to 0
+    pub max_retries: Option<u8>,
+    /// The first backoff step, doubled on every attempt, defaults to 1 second
+    pub backoff_base: Option<Duration>,
+    /// The longest single wait, defaults to 64 seconds
+    pub backoff_max: Option<Duration>,
+    /// Fraction (0 to 1) each wait may be randomly shortened by, defaults to 0
+    pub jitter: Option<f64>,
+    /// Total time to spend waiting for the node to find the transaction, defaults to 127 seconds.
+    /// Zero skips waiting entirely.
+    pub confirmation_timeout: Option<Duration>,
+    /// Whether a transaction the node never finds is an error, defaults to false
+    pub not_found_is_error: Option<bool>,
+    /// Whether to keep waiting after the transaction reaches the mempool until it is in a block, defaults to false
+    pub await_commit: Option<bool>,
+}
+
+impl Default for SubmitPolicyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubmitPolicyBuilder {
+    pub fn new() -> Self {
+        SubmitPolicyBuilder {
+            max_retries: None,
+            backoff_base: None,
+            backoff_max: None,
+            jitter: None,
+            confirmation_timeout: None,
+            not_found_is_error: None,
+            await_commit: None,
+        }
+    }
+    /// How many times to resubmit after a failed submission, defaults to 0
+    pub fn max_retries(mut self, max_retries: u8) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+    /// The first backoff step, doubled on every attempt, defaults to 1 second
+    pub fn backoff_base(mut self, backoff_base: Duration) -> Self {
+        self.backoff_base = Some(backoff_base);
+        self
+    }
+    /// The longest single wait, defaults to 64 seconds
+    pub fn backoff_max(mut self, backoff_max: Duration) -> Self {
+        self.backoff_max = Some(backoff_max);
+        self
+    }
+    /// Fraction (0 to 1) each wait may be randomly shortened by, defaults to 0
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+    /// Total time to spend waiting for the node to find the transaction, defaults to 127 seconds.
+    /// Zero skips waiting entirely.
+    pub fn confirmation_timeout(mut self, confirmation_timeout: Duration) -> Self {
+        self.confirmation_timeout = Some(confirmation_timeout);
+        self
+    }
+    /// Whether a transaction the node never finds is an error, defaults to false
+    pub fn not_found_is_error(mut self, not_found_is_error: bool) -> Self {
+        self.not_found_is_error = Some(not_found_is_error);
+        self
+    }
+    /// Whether to keep waiting after the transaction reaches the mempool until it is in a block, defaults to false
+    pub fn await_commit(mut self, await_commit: bool) -> Self {
+        self.await_commit = Some(await_commit);
+        self
+    }
+    /// Builds the SubmitPolicy
+    pub fn build(self) -> Result<SubmitPolicy, errors::DesoError> {
+        let default = SubmitPolicy::default();
+        let jitter = self.jitter.unwrap_or(default.jitter);
+        if !(0.0..=1.0).contains(&jitter) {
+            return Err(errors::DesoError::SubmitPolicyError(String::from(
+                "Jitter must be between 0 and 1",
+            )));
+        }
+        let backoff_base = self.backoff_base.unwrap_or(default.backoff_base);
+        let backoff_max = self.backoff_max.unwrap_or(default.backoff_max);
+        if backoff_max < backoff_base {
+            return Err(errors::DesoError::SubmitPolicyError(String::from(
+                "Backoff max must not be shorter than backoff base",
+            )));
+        }
+        Ok(SubmitPolicy {
+            max_retries: self.max_retries.unwrap_or(default.max_retries),
+            backoff_base,
+            backoff_max,
+            jitter,
+            confirmation_timeout: self
+                .confirmation_timeout
+                .unwrap_or(default.confirmation_timeout),
+            not_found_is_error: self
+                .not_found_is_error
+                .unwrap_or(default.not_found_is_error),
+            await_commit: self.await_commit.unwrap_or(default.await_commit),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = SubmitPolicyBuilder::new()
+            .backoff_base(Duration::from_secs(1))
+            .backoff_max(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let delays: Vec<u64> = (0..6).map(|i| policy.backoff(i).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+        assert_eq!(policy.backoff(200), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_in_range() {
+        let policy = SubmitPolicyBuilder::new().jitter(0.5).build().unwrap();
+        for _ in 0..100 {
+            let delay = policy.backoff(3);
+            assert!(delay > Duration::from_secs(4) && delay <= Duration::from_secs(8));
+        }
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        assert!(SubmitPolicyBuilder::new().jitter(1.5).build().is_err());
+        assert!(SubmitPolicyBuilder::new()
+            .backoff_base(Duration::from_secs(10))
+            .backoff_max(Duration::from_secs(1))
+            .build()
+            .is_err());
+    }
+}
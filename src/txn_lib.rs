@@ -0,0 +1,1258 @@
+use bitcoin_hashes::{sha256d, Hash};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::crypto_lib;
+use crate::errors;
+use crate::key_lib::PrivateKey;
+
+/// Length of a transaction or post hash
+pub const HASH_LEN: usize = 32;
+/// Length of a compressed secp256k1 public key
+pub const PUBLIC_KEY_LEN: usize = 33;
+
+/// Transaction type codes used on the wire, mirroring `TxnType` in the Deso core node
+pub const TXN_TYPE_BLOCK_REWARD: u64 = 1;
+pub const TXN_TYPE_BASIC_TRANSFER: u64 = 2;
+pub const TXN_TYPE_BITCOIN_EXCHANGE: u64 = 3;
+pub const TXN_TYPE_PRIVATE_MESSAGE: u64 = 4;
+pub const TXN_TYPE_SUBMIT_POST: u64 = 5;
+pub const TXN_TYPE_UPDATE_PROFILE: u64 = 6;
+pub const TXN_TYPE_UPDATE_BITCOIN_USD_EXCHANGE_RATE: u64 = 8;
+pub const TXN_TYPE_FOLLOW: u64 = 9;
+pub const TXN_TYPE_LIKE: u64 = 10;
+pub const TXN_TYPE_CREATOR_COIN: u64 = 11;
+pub const TXN_TYPE_SWAP_IDENTITY: u64 = 12;
+pub const TXN_TYPE_UPDATE_GLOBAL_PARAMS: u64 = 13;
+pub const TXN_TYPE_CREATOR_COIN_TRANSFER: u64 = 14;
+pub const TXN_TYPE_CREATE_NFT: u64 = 15;
+pub const TXN_TYPE_UPDATE_NFT: u64 = 16;
+pub const TXN_TYPE_ACCEPT_NFT_BID: u64 = 17;
+pub const TXN_TYPE_NFT_BID: u64 = 18;
+pub const TXN_TYPE_NFT_TRANSFER: u64 = 19;
+pub const TXN_TYPE_ACCEPT_NFT_TRANSFER: u64 = 20;
+pub const TXN_TYPE_BURN_NFT: u64 = 21;
+pub const TXN_TYPE_AUTHORIZE_DERIVED_KEY: u64 = 22;
+pub const TXN_TYPE_MESSAGING_GROUP: u64 = 23;
+pub const TXN_TYPE_DAO_COIN: u64 = 24;
+pub const TXN_TYPE_DAO_COIN_TRANSFER: u64 = 25;
+pub const TXN_TYPE_DAO_COIN_LIMIT_ORDER: u64 = 26;
+pub const TXN_TYPE_CREATE_USER_ASSOCIATION: u64 = 27;
+pub const TXN_TYPE_DELETE_USER_ASSOCIATION: u64 = 28;
+pub const TXN_TYPE_CREATE_POST_ASSOCIATION: u64 = 29;
+pub const TXN_TYPE_DELETE_POST_ASSOCIATION: u64 = 30;
+pub const TXN_TYPE_ACCESS_GROUP: u64 = 31;
+pub const TXN_TYPE_ACCESS_GROUP_MEMBERS: u64 = 32;
+pub const TXN_TYPE_NEW_MESSAGE: u64 = 33;
+pub const TXN_TYPE_REGISTER_AS_VALIDATOR: u64 = 34;
+pub const TXN_TYPE_UNREGISTER_AS_VALIDATOR: u64 = 35;
+pub const TXN_TYPE_STAKE: u64 = 36;
+pub const TXN_TYPE_UNSTAKE: u64 = 37;
+pub const TXN_TYPE_UNLOCK_STAKE: u64 = 38;
+pub const TXN_TYPE_UNJAIL_VALIDATOR: u64 = 39;
+pub const TXN_TYPE_COIN_LOCKUP: u64 = 40;
+pub const TXN_TYPE_UPDATE_COIN_LOCKUP_PARAMS: u64 = 41;
+pub const TXN_TYPE_COIN_LOCKUP_TRANSFER: u64 = 42;
+pub const TXN_TYPE_COIN_UNLOCK: u64 = 43;
+pub const TXN_TYPE_ATOMIC_TXNS_WRAPPER: u64 = 44;
+
+/// Whether `txn_type` is a transaction type the Deso core node knows. Code 7
+/// was never assigned.
+pub(crate) fn is_known_txn_type(txn_type: u64) -> bool {
+    matches!(txn_type, TXN_TYPE_BLOCK_REWARD..=TXN_TYPE_UPDATE_PROFILE)
+        || matches!(
+            txn_type,
+            TXN_TYPE_UPDATE_BITCOIN_USD_EXCHANGE_RATE..=TXN_TYPE_ATOMIC_TXNS_WRAPPER
+        )
+}
+
+/// Extra data key holding the derived public key a transaction is signed with
+pub const DERIVED_PUBLIC_KEY: &str = "DerivedPublicKey";
+/// Extra data key holding the hash of the post a submit post transaction reposts
+pub const REPOSTED_POST_HASH: &str = "RepostedPostHash";
+/// Extra data key holding the spending limit an authorize derived key transaction grants
+pub const TRANSACTION_SPENDING_LIMIT: &str = "TransactionSpendingLimit";
+
+/// Appends `value` as an unsigned LEB128 varint, the way Deso encodes lengths and integers
+pub(crate) fn encode_uvarint(value: u64, buf: &mut Vec<u8>) {
+    let mut value = value;
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+pub(crate) fn encode_byte_array(bytes: &[u8], buf: &mut Vec<u8>) {
+    encode_uvarint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn decoding_error(what: &str) -> errors::DesoError {
+    errors::DesoError::TxnDecodingError(String::from(what))
+}
+
+/// Reads Deso encoded fields off the front of a byte slice
+struct TxnReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TxnReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        TxnReader { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn read_uvarint(&mut self, what: &str) -> Result<u64, errors::DesoError> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = match self.bytes.get(self.pos) {
+                Some(b) => *b,
+                None => return Err(decoding_error(what)),
+            };
+            self.pos += 1;
+            if shift == 63 && byte > 1 {
+                return Err(decoding_error(what));
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err(decoding_error(what))
+    }
+
+    fn read_bytes(&mut self, len: usize, what: &str) -> Result<&'a [u8], errors::DesoError> {
+        let end = match self.pos.checked_add(len) {
+            Some(end) if end <= self.bytes.len() => end,
+            _ => return Err(decoding_error(what)),
+        };
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_byte_array(&mut self, what: &str) -> Result<&'a [u8], errors::DesoError> {
+        let len = self.read_uvarint(what)?;
+        if len > (self.bytes.len() - self.pos) as u64 {
+            return Err(decoding_error(what));
+        }
+        self.read_bytes(len as usize, what)
+    }
+
+    fn read_array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], errors::DesoError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N, what)?);
+        Ok(array)
+    }
+
+    fn read_bool(&mut self, what: &str) -> Result<bool, errors::DesoError> {
+        match self.read_bytes(1, what)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(decoding_error(what)),
+        }
+    }
+}
+
+/// A reference to an output of a previous transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesoInput {
+    pub txid: [u8; HASH_LEN],
+    pub index: u32,
+}
+
+/// DESO sent to a public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesoOutput {
+    pub public_key: [u8; PUBLIC_KEY_LEN],
+    pub amount_nanos: u64,
+}
+
+/// Replay protection for balance model (version 1) transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxnNonce {
+    /// The last block height the transaction may be mined in
+    pub expiration_block_height: u64,
+    /// Random id that makes otherwise identical transactions unique
+    pub partial_id: u64,
+}
+
+/// Metadata of a submit post transaction
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SubmitPostMetadata {
+    /// Hash of the post being edited, empty for a new post
+    pub post_hash_to_modify: Vec<u8>,
+    /// Hash of the post being commented on, empty for a top level post
+    pub parent_stake_id: Vec<u8>,
+    /// The JSON encoded body object
+    pub body: Vec<u8>,
+    pub creator_basis_points: u64,
+    pub stake_multiple_basis_points: u64,
+    pub timestamp_nanos: u64,
+    pub is_hidden: bool,
+}
+
+/// Metadata of a like or unlike transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LikeMetadata {
+    pub liked_post_hash: [u8; HASH_LEN],
+    pub is_unlike: bool,
+}
+
+/// Metadata of a transaction authorizing or revoking a derived key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizeDerivedKeyMetadata {
+    /// Compressed derived public key
+    pub derived_public_key: Vec<u8>,
+    pub expiration_block: u64,
+    /// 1 authorizes the key, 0 revokes it
+    pub operation_type: u8,
+    /// The owner's DER signature over the derived key, expiration and spending limit
+    pub access_signature: Vec<u8>,
+}
+
+/// The type specific part of a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxnMeta {
+    BasicTransfer,
+    SubmitPost(SubmitPostMetadata),
+    Like(LikeMetadata),
+    AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata),
+    /// Any other transaction type, kept as its raw metadata bytes
+    Other {
+        txn_type: u64,
+        bytes: Vec<u8>,
+    },
+}
+
+impl TxnMeta {
+    /// The wire code of this transaction type
+    pub fn txn_type(&self) -> u64 {
+        match self {
+            TxnMeta::BasicTransfer => TXN_TYPE_BASIC_TRANSFER,
+            TxnMeta::SubmitPost(_) => TXN_TYPE_SUBMIT_POST,
+            TxnMeta::Like(_) => TXN_TYPE_LIKE,
+            TxnMeta::AuthorizeDerivedKey(_) => TXN_TYPE_AUTHORIZE_DERIVED_KEY,
+            TxnMeta::Other { txn_type, .. } => *txn_type,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self {
+            TxnMeta::BasicTransfer => {}
+            TxnMeta::SubmitPost(meta) => {
+                encode_byte_array(&meta.post_hash_to_modify, &mut data);
+                encode_byte_array(&meta.parent_stake_id, &mut data);
+                encode_byte_array(&meta.body, &mut data);
+                encode_uvarint(meta.creator_basis_points, &mut data);
+                encode_uvarint(meta.stake_multiple_basis_points, &mut data);
+                encode_uvarint(meta.timestamp_nanos, &mut data);
+                data.push(meta.is_hidden as u8);
+            }
+            TxnMeta::Like(meta) => {
+                data.extend_from_slice(&meta.liked_post_hash);
+                data.push(meta.is_unlike as u8);
+            }
+            TxnMeta::AuthorizeDerivedKey(meta) => {
+                encode_byte_array(&meta.derived_public_key, &mut data);
+                encode_uvarint(meta.expiration_block, &mut data);
+                data.push(meta.operation_type);
+                encode_byte_array(&meta.access_signature, &mut data);
+            }
+            TxnMeta::Other { bytes, .. } => data.extend_from_slice(bytes),
+        }
+        data
+    }
+
+    fn from_bytes(txn_type: u64, bytes: &[u8]) -> Result<Self, errors::DesoError> {
+        let mut reader = TxnReader::new(bytes);
+        let meta = match txn_type {
+            TXN_TYPE_BASIC_TRANSFER => TxnMeta::BasicTransfer,
+            TXN_TYPE_SUBMIT_POST => TxnMeta::SubmitPost(SubmitPostMetadata {
+                post_hash_to_modify: reader.read_byte_array("PostHashToModify")?.to_vec(),
+                parent_stake_id: reader.read_byte_array("ParentStakeID")?.to_vec(),
+                body: reader.read_byte_array("Body")?.to_vec(),
+                creator_basis_points: reader.read_uvarint("CreatorBasisPoints")?,
+                stake_multiple_basis_points: reader.read_uvarint("StakeMultipleBasisPoints")?,
+                timestamp_nanos: reader.read_uvarint("TimestampNanos")?,
+                is_hidden: reader.read_bool("IsHidden")?,
+            }),
+            TXN_TYPE_LIKE => TxnMeta::Like(LikeMetadata {
+                liked_post_hash: reader.read_array("LikedPostHash")?,
+                is_unlike: reader.read_bool("IsUnlike")?,
+            }),
+            TXN_TYPE_AUTHORIZE_DERIVED_KEY => {
+                TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+                    derived_public_key: reader.read_byte_array("DerivedPublicKey")?.to_vec(),
+                    expiration_block: reader.read_uvarint("ExpirationBlock")?,
+                    operation_type: reader.read_bytes(1, "OperationType")?[0],
+                    access_signature: reader.read_byte_array("AccessSignature")?.to_vec(),
+                })
+            }
+            _ => {
+                return Ok(TxnMeta::Other {
+                    txn_type,
+                    bytes: bytes.to_vec(),
+                })
+            }
+        };
+        if !reader.is_empty() {
+            return Err(decoding_error("Unexpected bytes after metadata"));
+        }
+        Ok(meta)
+    }
+}
+
+/// What a transaction is expected to look like before it gets signed.
+///
+/// Unset fields are not checked. Outputs paying back to the transactor (change)
+/// are always allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxnExpectations {
+    /// Compressed public key of the expected transactor
+    pub public_key: Option<Vec<u8>>,
+    /// Expected transaction type code
+    pub txn_type: Option<u64>,
+    /// Public keys, besides the transactor, that may receive outputs
+    pub allowed_outputs: Vec<[u8; PUBLIC_KEY_LEN]>,
+    /// The highest fee the transaction may pay
+    pub max_fee_nanos: Option<u64>,
+    /// Compressed derived public key the transaction must carry in its extra data
+    pub derived_public_key: Option<Vec<u8>>,
+    /// What a submit post transaction must carry
+    pub post: Option<PostExpectations>,
+    /// The post a like transaction must like
+    pub liked_post_hash: Option<[u8; HASH_LEN]>,
+    /// Whether a like transaction must take the like back
+    pub is_unlike: Option<bool>,
+    /// The derived key an authorize derived key transaction must authorize or revoke
+    pub authorized_derived_public_key: Option<[u8; PUBLIC_KEY_LEN]>,
+    /// The expiration block an authorize derived key transaction must carry
+    pub expiration_block: Option<u64>,
+    /// The operation an authorize derived key transaction must carry: 1
+    /// authorizes the key, 0 revokes it
+    pub operation_type: Option<u8>,
+    /// The owner's access signature an authorize derived key transaction must carry
+    pub access_signature: Option<Vec<u8>>,
+    /// The encoded spending limit an authorize derived key transaction must grant
+    pub spending_limit: Option<Vec<u8>>,
+}
+
+/// What a submit post transaction is expected to carry.
+///
+/// Unlike `TxnExpectations`, every field is checked: an unset hash means the
+/// transaction must leave it empty, so a node can't turn a new post into an
+/// edit, a comment or a repost.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostExpectations {
+    /// The post being edited
+    pub post_hash_to_modify: Option<[u8; HASH_LEN]>,
+    /// The post, or profile public key, being commented on
+    pub parent_stake_id: Option<Vec<u8>>,
+    /// The post being reposted, carried in the extra data
+    pub reposted_post_hash: Option<[u8; HASH_LEN]>,
+    /// The text of the body object
+    pub body: String,
+    pub image_urls: Vec<String>,
+    pub video_urls: Vec<String>,
+    pub is_hidden: bool,
+}
+
+/// The body object of a submit post transaction. The node leaves out empty
+/// fields, so missing ones read as empty.
+#[derive(Deserialize)]
+struct PostBody {
+    #[serde(rename = "Body", default)]
+    body: String,
+    #[serde(rename = "ImageURLs", default)]
+    image_urls: Option<Vec<String>>,
+    #[serde(rename = "VideoURLs", default)]
+    video_urls: Option<Vec<String>>,
+}
+
+impl PostExpectations {
+    fn check(&self, txn: &MsgDeSoTxn) -> Result<(), String> {
+        let meta = match &txn.meta {
+            TxnMeta::SubmitPost(meta) => meta,
+            _ => return Err(String::from("Not a submit post transaction")),
+        };
+        let expected_target = self
+            .post_hash_to_modify
+            .as_ref()
+            .map_or(&[][..], |h| &h[..]);
+        if meta.post_hash_to_modify != expected_target {
+            return Err(format!(
+                "Modifies post {:?}, not {:?}",
+                hex::encode(&meta.post_hash_to_modify),
+                hex::encode(expected_target)
+            ));
+        }
+        let expected_parent = self.parent_stake_id.as_deref().unwrap_or_default();
+        if meta.parent_stake_id != expected_parent {
+            return Err(format!(
+                "Comments on {:?}, not {:?}",
+                hex::encode(&meta.parent_stake_id),
+                hex::encode(expected_parent)
+            ));
+        }
+        let reposted = txn.extra_data.get(REPOSTED_POST_HASH).map(|h| h.as_slice());
+        if reposted != self.reposted_post_hash.as_ref().map(|h| &h[..]) {
+            return Err(format!(
+                "Reposts {:?}, not {:?}",
+                reposted.map(hex::encode),
+                self.reposted_post_hash.map(hex::encode)
+            ));
+        }
+        let body: PostBody = match serde_json::from_slice(&meta.body) {
+            Ok(body) => body,
+            Err(e) => return Err(format!("Body is not a body object: {}", e)),
+        };
+        if body.body != self.body
+            || body.image_urls.unwrap_or_default() != self.image_urls
+            || body.video_urls.unwrap_or_default() != self.video_urls
+        {
+            return Err(String::from("Body is not the one requested"));
+        }
+        if meta.is_hidden != self.is_hidden {
+            return Err(format!(
+                "Hidden is {}, not {}",
+                meta.is_hidden, self.is_hidden
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A Deso transaction, mirroring `MsgDeSoTxn` in the Deso core node.
+///
+/// Transactions built here can be encoded, signed and submitted without
+/// asking a node to construct them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgDeSoTxn {
+    pub inputs: Vec<DesoInput>,
+    pub outputs: Vec<DesoOutput>,
+    pub meta: TxnMeta,
+    /// Compressed public key of the transactor
+    pub public_key: Vec<u8>,
+    /// Extra data, encoded sorted by key
+    pub extra_data: BTreeMap<String, Vec<u8>>,
+    /// DER encoded signature, `None` until signed
+    pub signature: Option<Vec<u8>>,
+    /// 0 for UTXO model transactions, 1 for balance model transactions
+    pub version: u64,
+    /// Only encoded for version 1 and up
+    pub fee_nanos: u64,
+    /// Required for version 1 and up
+    pub nonce: Option<TxnNonce>,
+}
+
+impl MsgDeSoTxn {
+    /// An unsigned balance model transaction from `public_key` with no fee set yet
+    pub fn new(public_key: [u8; PUBLIC_KEY_LEN], meta: TxnMeta, nonce: TxnNonce) -> Self {
+        MsgDeSoTxn {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            meta,
+            public_key: public_key.to_vec(),
+            extra_data: BTreeMap::new(),
+            signature: None,
+            version: 1,
+            fee_nanos: 0,
+            nonce: Some(nonce),
+        }
+    }
+
+    /// Encodes the transaction. With `pre_signature` the signature is left
+    /// out, which gives the bytes that get hashed and signed.
+    pub fn to_bytes(&self, pre_signature: bool) -> Result<Vec<u8>, errors::DesoError> {
+        let mut data = Vec::new();
+
+        encode_uvarint(self.inputs.len() as u64, &mut data);
+        for input in &self.inputs {
+            data.extend_from_slice(&input.txid);
+            encode_uvarint(input.index as u64, &mut data);
+        }
+
+        encode_uvarint(self.outputs.len() as u64, &mut data);
+        for output in &self.outputs {
+            data.extend_from_slice(&output.public_key);
+            encode_uvarint(output.amount_nanos, &mut data);
+        }
+
+        encode_uvarint(self.meta.txn_type(), &mut data);
+        encode_byte_array(&self.meta.to_bytes(), &mut data);
+
+        encode_byte_array(&self.public_key, &mut data);
+
+        encode_uvarint(self.extra_data.len() as u64, &mut data);
+        for (key, value) in &self.extra_data {
+            encode_byte_array(key.as_bytes(), &mut data);
+            encode_byte_array(value, &mut data);
+        }
+
+        match (&self.signature, pre_signature) {
+            (Some(signature), false) => encode_byte_array(signature, &mut data),
+            _ => encode_uvarint(0, &mut data),
+        }
+
+        if self.version != 0 {
+            let nonce = match &self.nonce {
+                Some(n) => n,
+                None => {
+                    return Err(errors::DesoError::TxnEncodingError(String::from(
+                        "Nonce is required for version 1 transactions",
+                    )))
+                }
+            };
+            encode_uvarint(self.version, &mut data);
+            encode_uvarint(self.fee_nanos, &mut data);
+            encode_uvarint(nonce.expiration_block_height, &mut data);
+            encode_uvarint(nonce.partial_id, &mut data);
+        }
+        Ok(data)
+    }
+
+    /// Hex of the full encoding, including the signature if there is one
+    pub fn to_hex(&self) -> Result<String, errors::DesoError> {
+        Ok(hex::encode(self.to_bytes(false)?))
+    }
+
+    /// Decodes a transaction, signed or not
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, errors::DesoError> {
+        Ok(Self::decode(bytes)?.0)
+    }
+
+    /// Where the signature length sits in an encoded transaction. For an
+    /// unsigned transaction this is the empty signature placeholder that
+    /// signing replaces.
+    pub fn signature_index(bytes: &[u8]) -> Result<usize, errors::DesoError> {
+        Ok(Self::decode(bytes)?.1)
+    }
+
+    /// Decodes a transaction along with its signature index
+    pub(crate) fn decode(bytes: &[u8]) -> Result<(Self, usize), errors::DesoError> {
+        let mut reader = TxnReader::new(bytes);
+
+        let input_count = reader.read_uvarint("Input count")?;
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let txid = reader.read_array("Input TxID")?;
+            let index = reader.read_uvarint("Input index")?;
+            if index > u32::MAX as u64 {
+                return Err(decoding_error("Input index"));
+            }
+            inputs.push(DesoInput {
+                txid,
+                index: index as u32,
+            });
+        }
+
+        let output_count = reader.read_uvarint("Output count")?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            outputs.push(DesoOutput {
+                public_key: reader.read_array("Output public key")?,
+                amount_nanos: reader.read_uvarint("Output amount")?,
+            });
+        }
+
+        let txn_type = reader.read_uvarint("Txn type")?;
+        let meta = TxnMeta::from_bytes(txn_type, reader.read_byte_array("Metadata")?)?;
+
+        let public_key = reader.read_byte_array("Public key")?.to_vec();
+
+        let extra_data_count = reader.read_uvarint("Extra data count")?;
+        let mut extra_data = BTreeMap::new();
+        for _ in 0..extra_data_count {
+            let key = match String::from_utf8(reader.read_byte_array("Extra data key")?.to_vec()) {
+                Ok(k) => k,
+                Err(_) => return Err(decoding_error("Extra data key")),
+            };
+            let value = reader.read_byte_array("Extra data value")?.to_vec();
+            extra_data.insert(key, value);
+        }
+
+        let signature_index = reader.pos;
+        let signature = reader.read_byte_array("Signature")?;
+        let signature = if signature.is_empty() {
+            None
+        } else {
+            Some(signature.to_vec())
+        };
+
+        let mut version = 0;
+        let mut fee_nanos = 0;
+        let mut nonce = None;
+        if !reader.is_empty() {
+            version = reader.read_uvarint("Version")?;
+            if version != 0 {
+                fee_nanos = reader.read_uvarint("Fee nanos")?;
+                nonce = Some(TxnNonce {
+                    expiration_block_height: reader.read_uvarint("Nonce expiration")?,
+                    partial_id: reader.read_uvarint("Nonce partial id")?,
+                });
+            }
+        }
+        if !reader.is_empty() {
+            return Err(decoding_error("Unexpected bytes after transaction"));
+        }
+
+        let txn = MsgDeSoTxn {
+            inputs,
+            outputs,
+            meta,
+            public_key,
+            extra_data,
+            signature,
+            version,
+            fee_nanos,
+            nonce,
+        };
+        Ok((txn, signature_index))
+    }
+
+    /// Decodes a transaction from hex, such as a node's `TransactionHex`
+    pub fn from_hex(txn_hex: &str) -> Result<Self, errors::DesoError> {
+        match hex::decode(txn_hex) {
+            Ok(bytes) => Self::from_bytes(&bytes),
+            Err(e) => Err(errors::DesoError::TxnDecodingError(e.to_string())),
+        }
+    }
+
+    /// The derived public key the transaction is signed with, from its extra data
+    pub fn derived_public_key(&self) -> Option<&[u8]> {
+        self.extra_data
+            .get(DERIVED_PUBLIC_KEY)
+            .map(|key| key.as_slice())
+    }
+
+    /// Checks the transaction against what the caller asked for
+    pub fn verify(&self, expected: &TxnExpectations) -> Result<(), errors::DesoError> {
+        let mismatch = |what: String| Err(errors::DesoError::TxnVerificationError(what));
+        if let Some(public_key) = &expected.public_key {
+            if &self.public_key != public_key {
+                return mismatch(format!(
+                    "Transactor {} is not {}",
+                    hex::encode(&self.public_key),
+                    hex::encode(public_key)
+                ));
+            }
+        }
+        if let Some(txn_type) = expected.txn_type {
+            if self.meta.txn_type() != txn_type {
+                return mismatch(format!(
+                    "Txn type {} is not {}",
+                    self.meta.txn_type(),
+                    txn_type
+                ));
+            }
+        }
+        for output in &self.outputs {
+            let is_change = output.public_key[..] == self.public_key[..];
+            if !is_change && !expected.allowed_outputs.contains(&output.public_key) {
+                return mismatch(format!(
+                    "Unexpected output of {} nanos to {}",
+                    output.amount_nanos,
+                    hex::encode(output.public_key)
+                ));
+            }
+        }
+        if let Some(max_fee_nanos) = expected.max_fee_nanos {
+            if self.fee_nanos > max_fee_nanos {
+                return mismatch(format!(
+                    "Fee of {} nanos is above the cap of {}",
+                    self.fee_nanos, max_fee_nanos
+                ));
+            }
+        }
+        if let Some(derived_public_key) = &expected.derived_public_key {
+            if self.derived_public_key() != Some(derived_public_key.as_slice()) {
+                return mismatch(String::from("Derived public key is missing or different"));
+            }
+        }
+        if let Some(post) = &expected.post {
+            if let Err(what) = post.check(self) {
+                return mismatch(what);
+            }
+        }
+        if let Some(liked_post_hash) = &expected.liked_post_hash {
+            match &self.meta {
+                TxnMeta::Like(meta) if meta.liked_post_hash == *liked_post_hash => {}
+                TxnMeta::Like(meta) => {
+                    return mismatch(format!(
+                        "Likes post {}, not {}",
+                        hex::encode(meta.liked_post_hash),
+                        hex::encode(liked_post_hash)
+                    ))
+                }
+                _ => return mismatch(String::from("Not a like transaction")),
+            }
+        }
+        if let Some(is_unlike) = expected.is_unlike {
+            match &self.meta {
+                TxnMeta::Like(meta) if meta.is_unlike == is_unlike => {}
+                TxnMeta::Like(meta) => {
+                    return mismatch(format!("Unlike is {}, not {}", meta.is_unlike, is_unlike))
+                }
+                _ => return mismatch(String::from("Not a like transaction")),
+            }
+        }
+        if expected.authorized_derived_public_key.is_some()
+            || expected.expiration_block.is_some()
+            || expected.operation_type.is_some()
+            || expected.access_signature.is_some()
+        {
+            let meta = match &self.meta {
+                TxnMeta::AuthorizeDerivedKey(meta) => meta,
+                _ => return mismatch(String::from("Not an authorize derived key transaction")),
+            };
+            if let Some(derived_public_key) = &expected.authorized_derived_public_key {
+                if meta.derived_public_key[..] != derived_public_key[..] {
+                    return mismatch(format!(
+                        "Authorizes derived key {}, not {}",
+                        hex::encode(&meta.derived_public_key),
+                        hex::encode(derived_public_key)
+                    ));
+                }
+            }
+            if let Some(expiration_block) = expected.expiration_block {
+                if meta.expiration_block != expiration_block {
+                    return mismatch(format!(
+                        "Expiration block {} is not {}",
+                        meta.expiration_block, expiration_block
+                    ));
+                }
+            }
+            if let Some(operation_type) = expected.operation_type {
+                if meta.operation_type != operation_type {
+                    return mismatch(format!(
+                        "Operation type {} is not {}",
+                        meta.operation_type, operation_type
+                    ));
+                }
+            }
+            if let Some(access_signature) = &expected.access_signature {
+                if meta.access_signature != *access_signature {
+                    return mismatch(String::from(
+                        "Access signature is not the one signed for this key",
+                    ));
+                }
+            }
+        }
+        if let Some(spending_limit) = &expected.spending_limit {
+            if self.extra_data.get(TRANSACTION_SPENDING_LIMIT) != Some(spending_limit) {
+                return mismatch(String::from("Spending limit is not the one requested"));
+            }
+        }
+        Ok(())
+    }
+
+    /// The transaction hash: double sha256 of the full encoding
+    pub fn hash(&self) -> Result<[u8; HASH_LEN], errors::DesoError> {
+        Ok(sha256d::Hash::hash(&self.to_bytes(false)?).into_inner())
+    }
+
+    /// The fee a node charging `fee_rate_nanos_per_kb` asks for this transaction,
+    /// sized as if it already carried a maximum length signature.
+    pub fn fee_for_rate(&self, fee_rate_nanos_per_kb: u64) -> Result<u64, errors::DesoError> {
+        let mut sized = self.clone();
+        sized.signature = Some(vec![0; crypto_lib::MAX_DER_SIGNATURE_LEN]);
+        // The fee is part of the encoding, so settle it until the size stops changing
+        let mut fee = 0;
+        loop {
+            sized.fee_nanos = fee;
+            let size = sized.to_bytes(false)?.len() as u64;
+            let next = (size * fee_rate_nanos_per_kb).div_ceil(1000);
+            if next <= fee {
+                return Ok(fee);
+            }
+            fee = next;
+        }
+    }
+
+    /// Signs the transaction with the owner seed or derived private key
+    pub fn sign(&mut self, private_key: &PrivateKey) -> Result<(), errors::DesoError> {
+        let unsigned = self.to_bytes(true)?;
+        self.signature = Some(crypto_lib::sign_bytes(&unsigned, private_key));
+        Ok(())
+    }
+
+    /// The hash that gets signed: double sha256 of the encoding without the signature
+    pub fn signing_hash(&self) -> Result<[u8; HASH_LEN], errors::DesoError> {
+        Ok(sha256d::Hash::hash(&self.to_bytes(true)?).into_inner())
+    }
+
+    /// Checks the transaction's signature against a compressed public key
+    pub fn verify_signature(&self, public_key: &[u8]) -> Result<(), errors::DesoError> {
+        let signature = match &self.signature {
+            Some(s) => s,
+            None => {
+                return Err(errors::DesoError::VerificationError(String::from(
+                    "Transaction is not signed",
+                )))
+            }
+        };
+        crypto_lib::verify_bytes(&self.to_bytes(true)?, signature, public_key)
+    }
+
+    /// Checks the transaction's signature against the key that should have
+    /// made it: the derived key named in its extra data, or else the transactor.
+    pub fn verify_signed(&self) -> Result<(), errors::DesoError> {
+        match self.derived_public_key() {
+            Some(derived_public_key) => self.verify_signature(derived_public_key),
+            None => self.verify_signature(&self.public_key),
+        }
+    }
+}
+
+/// Decodes a signed transaction hex and checks its signature, against
+/// `public_key` when given, or else the key the transaction says signed it.
+/// Returns the decoded transaction so callers can inspect what was signed.
+pub fn verify_signed_transaction(
+    signed_txn_hex: &str,
+    public_key: Option<&[u8]>,
+) -> Result<MsgDeSoTxn, errors::DesoError> {
+    let txn = MsgDeSoTxn::from_hex(signed_txn_hex)?;
+    match public_key {
+        Some(key) => txn.verify_signature(key)?,
+        None => txn.verify_signed()?,
+    }
+    Ok(txn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key() -> PrivateKey {
+        PrivateKey::from_hex("0101010101010101010101010101010101010101010101010101010101010101")
+            .unwrap()
+    }
+
+    const POSTER: [u8; PUBLIC_KEY_LEN] = [
+        0x02, 0x2b, 0x6c, 0x5a, 0x8e, 0x3d, 0x9f, 0x4b, 0x1a, 0x71, 0x0c, 0xe2, 0x55, 0x38, 0x90,
+        0xd4, 0x6f, 0x13, 0xa7, 0xbb, 0x04, 0xc8, 0x29, 0x5e, 0x66, 0xf0, 0x9a, 0x3c, 0x81, 0x47,
+        0xd2, 0x1e, 0x73,
+    ];
+
+    fn post_txn() -> MsgDeSoTxn {
+        let meta = TxnMeta::SubmitPost(SubmitPostMetadata {
+            body: br#"{"Body":"hi"}"#.to_vec(),
+            timestamp_nanos: 1_700_000_000_000_000_000,
+            ..Default::default()
+        });
+        let mut txn = MsgDeSoTxn::new(
+            POSTER,
+            meta,
+            TxnNonce {
+                expiration_block_height: 300_000,
+                partial_id: 7,
+            },
+        );
+        txn.fee_nanos = 168;
+        txn.extra_data
+            .insert(String::from("nft_type"), b"AUTHOR".to_vec());
+        txn.extra_data
+            .insert(String::from("App"), b"deso-rust".to_vec());
+        txn
+    }
+
+    #[test]
+    fn test_uvarint() {
+        let cases: [(u64, &str); 5] = [
+            (0, "00"),
+            (127, "7f"),
+            (128, "8001"),
+            (300, "ac02"),
+            (u64::MAX, "ffffffffffffffffff01"),
+        ];
+        for (value, expected) in cases {
+            let mut buf = Vec::new();
+            encode_uvarint(value, &mut buf);
+            assert_eq!(hex::encode(buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_submit_post() {
+        let expected = [
+            "00", // no inputs
+            "00", // no outputs
+            "05", // submit post
+            "1c", // metadata length
+            "00", // no post to modify
+            "00", // no parent
+            "0d", // body length
+            "7b22426f6479223a226869227d",
+            "00",                 // creator basis points
+            "00",                 // stake multiple basis points
+            "8080a8b1e39fe7cb17", // timestamp nanos
+            "00",                 // not hidden
+            "21",                 // public key length
+            "022b6c5a8e3d9f4b1a710ce2553890d46f13a7bb04c8295e66f09a3c8147d21e73",
+            "02", // extra data entries, sorted by key
+            "03",
+            "417070",
+            "09",
+            "6465736f2d72757374",
+            "08",
+            "6e66745f74797065",
+            "06",
+            "415554484f52",
+            "00",     // no signature
+            "01",     // version
+            "a801",   // fee nanos
+            "e0a712", // expiration block height
+            "07",     // partial id
+        ]
+        .concat();
+        assert_eq!(post_txn().to_hex().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_encode_like_and_signature() {
+        let mut txn = MsgDeSoTxn::new(
+            POSTER,
+            TxnMeta::Like(LikeMetadata {
+                liked_post_hash: [0xab; HASH_LEN],
+                is_unlike: true,
+            }),
+            TxnNonce {
+                expiration_block_height: 1,
+                partial_id: 2,
+            },
+        );
+        let unsigned = txn.to_bytes(true).unwrap();
+        assert_eq!(&unsigned[..4], &[0x00, 0x00, 0x0a, 0x21]);
+        assert_eq!(&unsigned[4..36], &[0xab; HASH_LEN]);
+        assert_eq!(unsigned[36], 0x01);
+
+        txn.signature = Some(vec![0x30, 0x01, 0x02]);
+        let signed = txn.to_bytes(false).unwrap();
+        assert_eq!(signed.len(), unsigned.len() + 3);
+        // Signing bytes never include the signature
+        assert_eq!(txn.to_bytes(true).unwrap(), unsigned);
+    }
+
+    #[test]
+    fn test_decode_round_trip() {
+        let mut txn = post_txn();
+        let unsigned_hex = txn.to_hex().unwrap();
+        assert_eq!(MsgDeSoTxn::from_hex(&unsigned_hex).unwrap(), txn);
+
+        txn.inputs.push(DesoInput {
+            txid: [9; HASH_LEN],
+            index: 300,
+        });
+        txn.outputs.push(DesoOutput {
+            public_key: POSTER,
+            amount_nanos: 5_000,
+        });
+        txn.sign(&test_key()).unwrap();
+        let signed = txn.to_bytes(false).unwrap();
+        assert_eq!(MsgDeSoTxn::from_bytes(&signed).unwrap(), txn);
+
+        let mut old = txn.clone();
+        old.version = 0;
+        old.fee_nanos = 0;
+        old.nonce = None;
+        assert_eq!(
+            MsgDeSoTxn::from_bytes(&old.to_bytes(false).unwrap()).unwrap(),
+            old
+        );
+
+        let unknown = TxnMeta::Other {
+            txn_type: 33,
+            bytes: vec![1, 2, 3],
+        };
+        let mut other = post_txn();
+        other.meta = unknown;
+        assert_eq!(
+            MsgDeSoTxn::from_bytes(&other.to_bytes(false).unwrap()).unwrap(),
+            other
+        );
+    }
+
+    /// Reads `testdata/txns/<name>.hex`, transaction hex captured from a node
+    fn node_fixture(name: &str) -> String {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/txns")
+            .join(format!("{}.hex", name));
+        match std::fs::read_to_string(&path) {
+            Ok(hex) => hex.trim().to_string(),
+            Err(e) => panic!("{}: {}, see testdata/README.md", path.display(), e),
+        }
+    }
+
+    #[test]
+    #[ignore = "needs transaction hex captured from a node, see testdata/README.md"]
+    fn test_node_built_round_trip() {
+        for (name, txn_type) in [
+            ("submit_post", TXN_TYPE_SUBMIT_POST),
+            ("like", TXN_TYPE_LIKE),
+            ("authorize_derived_key", TXN_TYPE_AUTHORIZE_DERIVED_KEY),
+        ] {
+            let node_hex = node_fixture(name);
+            let txn = MsgDeSoTxn::from_hex(&node_hex).unwrap();
+            assert_eq!(txn.meta.txn_type(), txn_type, "{}", name);
+            assert!(!matches!(txn.meta, TxnMeta::Other { .. }), "{}", name);
+            assert_eq!(txn.to_hex().unwrap(), node_hex, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let key = test_key();
+        let signer =
+            hex::decode("031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f")
+                .unwrap();
+        let mut txn = post_txn();
+        txn.public_key = signer.clone();
+        assert!(txn.verify_signed().is_err());
+
+        txn.sign(&key).unwrap();
+        assert!(txn.verify_signed().is_ok());
+        assert!(txn.verify_signature(&signer).is_ok());
+        assert!(txn.verify_signature(&POSTER).is_err());
+        let signed_hex = txn.to_hex().unwrap();
+        assert_eq!(verify_signed_transaction(&signed_hex, None).unwrap(), txn);
+        assert!(verify_signed_transaction(&signed_hex, Some(&POSTER)).is_err());
+
+        // Any change to the signed fields breaks the signature
+        let mut tampered = txn.clone();
+        tampered.fee_nanos += 1;
+        assert!(tampered.verify_signed().is_err());
+
+        // A derived key signature is checked against the key in the extra data
+        let mut derived = txn.clone();
+        derived.public_key = POSTER.to_vec();
+        derived
+            .extra_data
+            .insert(String::from(DERIVED_PUBLIC_KEY), signer.clone());
+        derived.sign(&key).unwrap();
+        assert!(derived.verify_signed().is_ok());
+        assert!(derived.verify_signature(&POSTER).is_err());
+    }
+
+    #[test]
+    fn test_signature_index() {
+        let txn = post_txn();
+        let unsigned = txn.to_bytes(true).unwrap();
+        // Version, fee and nonce follow the empty signature
+        assert_eq!(
+            MsgDeSoTxn::signature_index(&unsigned).unwrap(),
+            unsigned.len() - 8
+        );
+        assert_eq!(unsigned[unsigned.len() - 8], 0);
+
+        let mut signed = txn.clone();
+        signed.sign(&test_key()).unwrap();
+        let signed = signed.to_bytes(false).unwrap();
+        assert_eq!(
+            MsgDeSoTxn::signature_index(&signed).unwrap(),
+            unsigned.len() - 8
+        );
+
+        let mut old = txn;
+        old.version = 0;
+        let old = old.to_bytes(true).unwrap();
+        assert_eq!(MsgDeSoTxn::signature_index(&old).unwrap(), old.len() - 1);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_bytes() {
+        let txn = post_txn();
+        let bytes = txn.to_bytes(false).unwrap();
+        // Cutting off just the version 1 fields leaves a valid version 0 transaction
+        let mut old = txn.clone();
+        old.version = 0;
+        let old_len = old.to_bytes(false).unwrap().len();
+        for len in (0..bytes.len()).filter(|len| *len != old_len) {
+            assert!(MsgDeSoTxn::from_bytes(&bytes[..len]).is_err());
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(MsgDeSoTxn::from_bytes(&trailing).is_err());
+        assert!(MsgDeSoTxn::from_hex("zz").is_err());
+        // A huge length prefix must not be trusted
+        assert!(MsgDeSoTxn::from_bytes(&[0xff, 0xff, 0xff, 0xff, 0x0f]).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut txn = post_txn();
+        let expected = TxnExpectations {
+            public_key: Some(POSTER.to_vec()),
+            txn_type: Some(TXN_TYPE_SUBMIT_POST),
+            max_fee_nanos: Some(200),
+            ..Default::default()
+        };
+        assert!(txn.verify(&expected).is_ok());
+
+        let mut wrong_type = expected.clone();
+        wrong_type.txn_type = Some(TXN_TYPE_LIKE);
+        assert!(txn.verify(&wrong_type).is_err());
+
+        let mut low_cap = expected.clone();
+        low_cap.max_fee_nanos = Some(100);
+        assert!(txn.verify(&low_cap).is_err());
+
+        // Change back to the poster is fine, paying someone else is not
+        txn.outputs.push(DesoOutput {
+            public_key: POSTER,
+            amount_nanos: 10,
+        });
+        assert!(txn.verify(&expected).is_ok());
+        let stranger = [3; PUBLIC_KEY_LEN];
+        txn.outputs.push(DesoOutput {
+            public_key: stranger,
+            amount_nanos: 10,
+        });
+        assert!(txn.verify(&expected).is_err());
+        let mut allowed = expected.clone();
+        allowed.allowed_outputs.push(stranger);
+        assert!(txn.verify(&allowed).is_ok());
+
+        let mut derived = allowed.clone();
+        derived.derived_public_key = Some(vec![3; PUBLIC_KEY_LEN]);
+        assert!(txn.verify(&derived).is_err());
+        txn.extra_data
+            .insert(String::from(DERIVED_PUBLIC_KEY), vec![3; PUBLIC_KEY_LEN]);
+        assert!(txn.verify(&derived).is_ok());
+
+        // Post checks cover the whole post, leaving nothing for the node to fill in
+        let mut post = allowed.clone();
+        post.post = Some(PostExpectations {
+            body: String::from("hi"),
+            ..Default::default()
+        });
+        assert!(txn.verify(&post).is_ok());
+        let post_checks = |txn: &MsgDeSoTxn, change: &dyn Fn(&mut PostExpectations)| {
+            let mut changed = post.clone();
+            change(changed.post.as_mut().unwrap());
+            txn.verify(&changed)
+        };
+        assert!(post_checks(&txn, &|p| p.body = String::from("bye")).is_err());
+        assert!(post_checks(&txn, &|p| p.image_urls = vec![String::from("a")]).is_err());
+        assert!(post_checks(&txn, &|p| p.is_hidden = true).is_err());
+        assert!(post_checks(&txn, &|p| p.parent_stake_id = Some(vec![6; HASH_LEN])).is_err());
+        // A new post modifies nothing, so it can't pass for an edit, nor the other way round
+        let edit = |p: &mut PostExpectations| p.post_hash_to_modify = Some([7; HASH_LEN]);
+        assert!(post_checks(&txn, &edit).is_err());
+        let mut edit_txn = txn.clone();
+        if let TxnMeta::SubmitPost(meta) = &mut edit_txn.meta {
+            meta.post_hash_to_modify = vec![7; HASH_LEN];
+        }
+        assert!(post_checks(&edit_txn, &edit).is_ok());
+        assert!(edit_txn.verify(&post).is_err());
+        let mut repost_txn = txn.clone();
+        repost_txn
+            .extra_data
+            .insert(String::from(REPOSTED_POST_HASH), vec![8; HASH_LEN]);
+        let repost = |p: &mut PostExpectations| p.reposted_post_hash = Some([8; HASH_LEN]);
+        assert!(post_checks(&txn, &repost).is_err());
+        assert!(post_checks(&repost_txn, &repost).is_ok());
+        assert!(repost_txn.verify(&post).is_err());
+        let mut unreadable = txn.clone();
+        if let TxnMeta::SubmitPost(meta) = &mut unreadable.meta {
+            meta.body = b"hi".to_vec();
+        }
+        assert!(unreadable.verify(&post).is_err());
+
+        // Likes are checked against the post they like
+        let mut like = TxnExpectations {
+            liked_post_hash: Some([7; HASH_LEN]),
+            ..Default::default()
+        };
+        let mut like_txn = post_txn();
+        like_txn.meta = TxnMeta::Like(LikeMetadata {
+            liked_post_hash: [7; HASH_LEN],
+            is_unlike: false,
+        });
+        assert!(like_txn.verify(&like).is_ok());
+        assert!(like_txn.verify(&post).is_err());
+        like.is_unlike = Some(true);
+        assert!(like_txn.verify(&like).is_err());
+        like.is_unlike = Some(false);
+        assert!(like_txn.verify(&like).is_ok());
+        assert!(txn.verify(&like).is_err());
+        like.liked_post_hash = Some([8; HASH_LEN]);
+        assert!(like_txn.verify(&like).is_err());
+
+        // Authorizations are checked against the key, expiration and operation
+        let mut revoke = TxnExpectations {
+            authorized_derived_public_key: Some([4; PUBLIC_KEY_LEN]),
+            expiration_block: Some(5000),
+            operation_type: Some(0),
+            ..Default::default()
+        };
+        assert!(like_txn.verify(&revoke).is_err());
+        let mut authorize_txn = post_txn();
+        authorize_txn.meta = TxnMeta::AuthorizeDerivedKey(AuthorizeDerivedKeyMetadata {
+            derived_public_key: vec![4; PUBLIC_KEY_LEN],
+            expiration_block: 5000,
+            operation_type: 1,
+            access_signature: vec![],
+        });
+        assert!(authorize_txn.verify(&revoke).is_err());
+        revoke.operation_type = Some(1);
+        assert!(authorize_txn.verify(&revoke).is_ok());
+        revoke.expiration_block = Some(5001);
+        assert!(authorize_txn.verify(&revoke).is_err());
+        revoke.expiration_block = Some(5000);
+        revoke.authorized_derived_public_key = Some([5; PUBLIC_KEY_LEN]);
+        assert!(authorize_txn.verify(&revoke).is_err());
+        revoke.authorized_derived_public_key = Some([4; PUBLIC_KEY_LEN]);
+        revoke.access_signature = Some(vec![1, 2]);
+        assert!(authorize_txn.verify(&revoke).is_err());
+        if let TxnMeta::AuthorizeDerivedKey(meta) = &mut authorize_txn.meta {
+            meta.access_signature = vec![1, 2];
+        }
+        assert!(authorize_txn.verify(&revoke).is_ok());
+        revoke.spending_limit = Some(vec![9]);
+        assert!(authorize_txn.verify(&revoke).is_err());
+        authorize_txn
+            .extra_data
+            .insert(String::from(TRANSACTION_SPENDING_LIMIT), vec![9]);
+        assert!(authorize_txn.verify(&revoke).is_ok());
+
+        let mut other_poster = expected;
+        other_poster.public_key = Some(stranger.to_vec());
+        assert!(txn.verify(&other_poster).is_err());
+    }
+
+    #[test]
+    fn test_version_one_requires_nonce() {
+        let mut txn = post_txn();
+        txn.nonce = None;
+        assert!(txn.to_bytes(true).is_err());
+        txn.version = 0;
+        assert!(txn.to_bytes(true).is_ok());
+    }
+
+    #[test]
+    fn test_fee_for_rate() {
+        let txn = post_txn();
+        let fee = txn.fee_for_rate(1000).unwrap();
+        let mut sized = txn.clone();
+        sized.fee_nanos = fee;
+        sized.signature = Some(vec![0; crypto_lib::MAX_DER_SIGNATURE_LEN]);
+        assert_eq!(fee, sized.to_bytes(false).unwrap().len() as u64);
+        assert_eq!(txn.fee_for_rate(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sign() {
+        let mut txn = post_txn();
+        txn.sign(&test_key()).unwrap();
+        let signature = txn.signature.clone().unwrap();
+        assert_eq!(signature[0], 0x30);
+        assert!(signature.len() <= crypto_lib::MAX_DER_SIGNATURE_LEN);
+    }
+}